@@ -26,8 +26,12 @@ pub struct Environment {
 	scroll: usize,
 	batch:  Option<u32>,
 
-	x11:   X11,
-	cocoa: Cocoa,
+	synchronize: u32,
+
+	device: Device,
+	report: Report,
+	x11:    X11,
+	cocoa:  Cocoa,
 }
 
 impl Default for Environment {
@@ -40,8 +44,46 @@ impl Default for Environment {
 			scroll: 4096,
 			batch:  Some(16),
 
-			x11:   Default::default(),
-			cocoa: Default::default(),
+			synchronize: 1000,
+
+			device: Default::default(),
+			report: Default::default(),
+			x11:    Default::default(),
+			cocoa:  Default::default(),
+		}
+	}
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Device {
+	level:      u8,
+	features:   Vec<u32>,
+	id:         String,
+	answerback: String,
+}
+
+impl Default for Device {
+	fn default() -> Self {
+		Device {
+			level:      4,
+			features:   vec![6, 21],
+			id:         "00000000".into(),
+			answerback: "".into(),
+		}
+	}
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Report {
+	clipboard: bool,
+	title:     bool,
+}
+
+impl Default for Report {
+	fn default() -> Self {
+		Report {
+			clipboard: false,
+			title:     false,
 		}
 	}
 }
@@ -120,6 +162,47 @@ impl Environment {
 			}
 		}
 
+		if let Some(value) = table.get("synchronize").and_then(|v| v.as_integer()) {
+			if value >= 0 {
+				self.synchronize = value as u32;
+			}
+		}
+
+		if let Some(table) = table.get("device").and_then(|v| v.as_table()) {
+			if let Some(value) = table.get("level").and_then(|v| v.as_integer()) {
+				if value >= 1 && value <= 5 {
+					self.device.level = value as u8;
+				}
+			}
+
+			if let Some(value) = table.get("features").and_then(|v| v.as_slice()) {
+				self.device.features = value.iter()
+					.filter_map(|v| v.as_integer())
+					.map(|v| v as u32)
+					.collect();
+			}
+
+			if let Some(value) = table.get("id").and_then(|v| v.as_str()) {
+				if value.len() <= 8 && value.chars().all(|c| c.is_digit(16)) {
+					self.device.id = format!("{:0>8}", value.to_uppercase());
+				}
+			}
+
+			if let Some(value) = table.get("answerback").and_then(|v| v.as_str()) {
+				self.device.answerback = value.into();
+			}
+		}
+
+		if let Some(table) = table.get("report").and_then(|v| v.as_table()) {
+			if let Some(value) = table.get("clipboard").and_then(|v| v.as_bool()) {
+				self.report.clipboard = value;
+			}
+
+			if let Some(value) = table.get("title").and_then(|v| v.as_bool()) {
+				self.report.title = value;
+			}
+		}
+
 		if let Some(table) = table.get("x11").and_then(|v| v.as_table()) {
 			if let Some(value) = table.get("display").and_then(|v| v.as_str()) {
 				self.x11.display = Some(value.into());
@@ -157,6 +240,18 @@ impl Environment {
 		self.batch
 	}
 
+	pub fn synchronize(&self) -> u32 {
+		self.synchronize
+	}
+
+	pub fn device(&self) -> &Device {
+		&self.device
+	}
+
+	pub fn report(&self) -> &Report {
+		&self.report
+	}
+
 	pub fn x11(&self) -> &X11 {
 		&self.x11
 	}
@@ -166,6 +261,34 @@ impl Environment {
 	}
 }
 
+impl Device {
+	pub fn level(&self) -> u8 {
+		self.level
+	}
+
+	pub fn features(&self) -> &[u32] {
+		&self.features
+	}
+
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	pub fn answerback(&self) -> &str {
+		&self.answerback
+	}
+}
+
+impl Report {
+	pub fn clipboard(&self) -> bool {
+		self.clipboard
+	}
+
+	pub fn title(&self) -> bool {
+		self.title
+	}
+}
+
 impl X11 {
 	pub fn display(&self) -> Option<&str> {
 		self.display.as_ref().map(AsRef::as_ref)
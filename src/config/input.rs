@@ -28,7 +28,7 @@ pub struct Input {
 impl Default for Input {
 	fn default() -> Self {
 		Input {
-			prefix: Key::new("a".to_string().into(), key::LOGO, Default::default()),
+			prefix: Key::new("a".to_string().into(), key::LOGO, Default::default(), Default::default()),
 			mouse:  true,
 			locale: None,
 		}
@@ -124,5 +124,5 @@ fn to_key<T: AsRef<str>>(value: T) -> Key {
 			button.into()
 	};
 
-	Key::new(key, modifiers, Default::default())
+	Key::new(key, modifiers, Default::default(), Default::default())
 }
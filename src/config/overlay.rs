@@ -29,6 +29,7 @@ use style::{self, Style};
 pub struct Overlay {
 	pub(super) cursor:    Cursor,
 	pub(super) status:    Option<Style>,
+	pub(super) directory: bool,
 	pub(super) selection: Style,
 
 	pub(super) hinter:  Hinter,
@@ -44,12 +45,16 @@ impl Default for Overlay {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
 				attributes: style::NONE,
+				underline:  None,
 			}),
 
+			directory: false,
+
 			selection: Style {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
 				attributes: style::NONE,
+				underline:  None,
 			},
 
 			hinter:  Default::default(),
@@ -78,6 +83,7 @@ impl Default for Hinter {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
 				attributes: style::BOLD,
+				underline:  None,
 			},
 		}
 	}
@@ -130,6 +136,10 @@ impl Overlay {
 					status.attributes = to_attributes(value);
 				}
 
+				if let Some(value) = table.get("directory").and_then(|v| v.as_bool()) {
+					self.directory = value;
+				}
+
 				self.status = Some(status);
 			}
 			else {
@@ -247,6 +257,10 @@ impl Overlay {
 		self.status.as_ref()
 	}
 
+	pub fn directory(&self) -> bool {
+		self.directory
+	}
+
 	pub fn selection(&self) -> &Style {
 		&self.selection
 	}
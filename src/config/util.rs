@@ -25,6 +25,10 @@ pub fn is_color(arg: &str) -> bool {
 }
 
 pub fn to_color(arg: &str) -> Option<Rgba<f64>> {
+	if arg.starts_with("rgb:") {
+		return to_rgb(&arg[4..]);
+	}
+
 	if !is_color(arg) {
 		return None;
 	}
@@ -60,6 +64,32 @@ pub fn to_color(arg: &str) -> Option<Rgba<f64>> {
 	Some(Rgba::new_u8(r, g, b, a))
 }
 
+/// Parse an X11 `r/g/b` color specification, where every component is made
+/// of one to four hexadecimal digits.
+fn to_rgb(arg: &str) -> Option<Rgba<f64>> {
+	let mut components = [0.0; 3];
+	let mut parts      = arg.split('/');
+
+	for component in &mut components {
+		let part = try!(option parts.next());
+
+		if part.is_empty() || part.len() > 4 {
+			return None;
+		}
+
+		let value = try!(option u16::from_str_radix(part, 16).ok());
+		let max   = (1u32 << (part.len() * 4)) - 1;
+
+		*component = value as f64 / max as f64;
+	}
+
+	if parts.next().is_some() {
+		return None;
+	}
+
+	Some(Rgba::new(components[0], components[1], components[2], 1.0))
+}
+
 pub fn to_attributes(arg: &str) -> style::Attributes {
 	let mut attributes = style::NONE;
 
@@ -69,6 +99,7 @@ pub fn to_attributes(arg: &str) -> style::Attributes {
 			"faint"     => style::FAINT,
 			"italic"    => style::ITALIC,
 			"underline" => style::UNDERLINE,
+			"overline"  => style::OVERLINE,
 			"blink"     => style::BLINK,
 			"reverse"   => style::REVERSE,
 			"invisible" => style::INVISIBLE,
@@ -16,7 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::os::raw::c_void;
-use libc::{c_int, c_uchar, c_double};
+use libc::{c_int, c_uint, c_char, c_uchar, c_double};
 
 #[repr(C)]
 pub struct cairo_t(c_void);
@@ -30,6 +30,13 @@ pub struct cairo_surface_t(c_void);
 #[repr(C)]
 pub struct cairo_pattern_t(c_void);
 
+pub type cairo_status_t = c_int;
+
+pub const CAIRO_STATUS_SUCCESS:    cairo_status_t = 0;
+pub const CAIRO_STATUS_READ_ERROR: cairo_status_t = 10;
+
+pub type cairo_read_func_t = extern "C" fn(closure: *mut c_void, data: *mut c_uchar, length: c_uint) -> cairo_status_t;
+
 #[repr(C)]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum cairo_format_t {
@@ -42,6 +49,53 @@ pub enum cairo_format_t {
 	Rgb30,
 }
 
+#[repr(C)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum cairo_operator_t {
+	Clear,
+	Source,
+	Over,
+	In,
+	Out,
+	Atop,
+	Dest,
+	DestOver,
+	DestIn,
+	DestOut,
+	DestAtop,
+	Xor,
+	Add,
+	Saturate,
+	Multiply,
+	Screen,
+	Overlay,
+	Darken,
+	Lighten,
+	ColorDodge,
+	ColorBurn,
+	HardLight,
+	SoftLight,
+	Difference,
+	Exclusion,
+	HslHue,
+	HslSaturation,
+	HslColor,
+	HslLuminosity,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct cairo_text_extents_t {
+	pub x_bearing: c_double,
+	pub y_bearing: c_double,
+
+	pub width:  c_double,
+	pub height: c_double,
+
+	pub x_advance: c_double,
+	pub y_advance: c_double,
+}
+
 #[repr(C)]
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub struct cairo_matrix_t {
@@ -69,23 +123,44 @@ extern "C" {
 	pub fn cairo_save(cr: *mut cairo_t);
 	pub fn cairo_restore(cr: *mut cairo_t);
 
+	pub fn cairo_translate(cr: *mut cairo_t, tx: c_double, ty: c_double);
+	pub fn cairo_scale(cr: *mut cairo_t, sx: c_double, sy: c_double);
+
 	pub fn cairo_paint(cr: *mut cairo_t);
 	pub fn cairo_set_source(cr: *mut cairo_t, pattern: *mut cairo_pattern_t);
 	pub fn cairo_set_source_rgb(cr: *mut cairo_t, r: c_double, g: c_double, b: c_double);
 	pub fn cairo_set_source_rgba(cr: *mut cairo_t, r: c_double, g: c_double, b: c_double, a: c_double);
 	pub fn cairo_set_source_surface(cr: *mut cairo_t, surface: *const cairo_surface_t, x: c_double, y: c_double);
 
+	pub fn cairo_set_operator(cr: *mut cairo_t, op: cairo_operator_t);
+
 	pub fn cairo_fill(cr: *mut cairo_t);
 	pub fn cairo_stroke(cr: *mut cairo_t);
 
+	pub fn cairo_new_path(cr: *mut cairo_t);
+	pub fn cairo_close_path(cr: *mut cairo_t);
 	pub fn cairo_move_to(cr: *mut cairo_t, x: c_double, y: c_double);
 	pub fn cairo_line_to(cr: *mut cairo_t, x: c_double, y: c_double);
+	pub fn cairo_curve_to(cr: *mut cairo_t, x1: c_double, y1: c_double, x2: c_double, y2: c_double, x3: c_double, y3: c_double);
+	pub fn cairo_arc(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
+	pub fn cairo_arc_negative(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
 	pub fn cairo_set_line_width(cr: *mut cairo_t, w: c_double);
 
+	pub fn cairo_set_font_size(cr: *mut cairo_t, size: c_double);
+	pub fn cairo_show_text(cr: *mut cairo_t, utf8: *const c_char);
+	pub fn cairo_text_extents(cr: *mut cairo_t, utf8: *const c_char, extents: *mut cairo_text_extents_t);
+
 	pub fn cairo_clip(cr: *mut cairo_t);
 	pub fn cairo_rectangle(cr: *mut cairo_t, x: c_double, y: c_double, w: c_double, h: c_double);
 
 	pub fn cairo_image_surface_create_for_data(data: *const c_uchar, format: cairo_format_t, width: c_int, height: c_int, stride: c_int) -> *mut cairo_surface_t;
+	pub fn cairo_image_surface_create_from_png_stream(read: cairo_read_func_t, closure: *mut c_void) -> *mut cairo_surface_t;
+	pub fn cairo_image_surface_get_data(surface: *mut cairo_surface_t) -> *mut c_uchar;
+	pub fn cairo_image_surface_get_format(surface: *mut cairo_surface_t) -> cairo_format_t;
+	pub fn cairo_image_surface_get_width(surface: *mut cairo_surface_t) -> c_int;
+	pub fn cairo_image_surface_get_height(surface: *mut cairo_surface_t) -> c_int;
+	pub fn cairo_image_surface_get_stride(surface: *mut cairo_surface_t) -> c_int;
+	pub fn cairo_surface_status(surface: *mut cairo_surface_t) -> cairo_status_t;
 	pub fn cairo_surface_flush(surface: *mut cairo_surface_t);
 	pub fn cairo_surface_destroy(surface: *mut cairo_surface_t);
 
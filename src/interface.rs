@@ -17,14 +17,18 @@
 
 use std::ops::Index;
 use std::io::Write;
+use std::rc::Rc;
+use std::path::Path;
 use std::vec;
 
 use picto::Region;
 use error;
 use config::Config;
 use platform::{Clipboard, Key, Mouse};
-use terminal::{Terminal, Mode, Iter, Cell};
-use terminal::{cursor, touched};
+use platform::key;
+use terminal::{Terminal, Mode, Palette, Link, Iter, Cell};
+use terminal::{cursor, touched, row};
+use terminal::graphics::Placement;
 use overlay::Overlay;
 
 #[derive(Debug)]
@@ -39,6 +43,8 @@ pub enum Action {
 	Overlay(bool),
 	Title(String),
 	Resize(u32, u32),
+	Iconify(bool),
+	Raise,
 	Copy(Clipboard, String),
 	Paste(Clipboard),
 	Open(Option<String>, String),
@@ -114,6 +120,56 @@ impl Interface {
 		}
 	}
 
+	pub fn palette(&self) -> &Palette {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.palette(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.palette(),
+		}
+	}
+
+	pub fn hover(&self) -> Option<&Rc<Link>> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.hover(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.hover(),
+		}
+	}
+
+	pub fn size(&self, y: u32) -> row::Size {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.size(y),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.size(y),
+		}
+	}
+
+	pub fn images(&self) -> Vec<(i32, Rc<Placement>)> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.images(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.images(),
+		}
+	}
+
+	pub fn cwd(&self) -> Option<&Path> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.cwd(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.cwd(),
+		}
+	}
+
 	pub fn region(&self) -> Region {
 		match *self {
 			Interface::Terminal(ref terminal) =>
@@ -174,10 +230,12 @@ impl Interface {
 		}
 
 		match *self {
-			Interface::Terminal(ref mut terminal) => {
-				try!(terminal.key(key, output));
-				Ok((Vec::new().into_iter(), touched::Iter::empty()))
-			}
+			Interface::Terminal(ref mut terminal) =>
+				terminal.key(key, output),
+
+			// The overlay only cares about presses.
+			Interface::Overlay(_) if key.state() == key::State::Release =>
+				Ok((Vec::new().into_iter(), touched::Iter::empty())),
 
 			Interface::Overlay(ref mut overlay) => {
 				Ok(overlay.key(key))
@@ -192,8 +250,7 @@ impl Interface {
 
 		match *self {
 			Interface::Terminal(ref mut terminal) => {
-				try!(terminal.mouse(mouse, output));
-				Ok((Vec::new().into_iter(), touched::Iter::empty()))
+				Ok((Vec::new().into_iter(), try!(terminal.mouse(mouse, output))))
 			}
 
 			Interface::Overlay(ref mut overlay) => {
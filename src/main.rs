@@ -41,6 +41,7 @@ extern crate schedule_recv as timer;
 extern crate picto;
 #[macro_use(arg)]
 extern crate control_code as control;
+extern crate base64;
 
 extern crate unicode_segmentation;
 extern crate unicode_width;
@@ -196,6 +197,7 @@ fn main() {
 		let (_batcher, mut batch) = channel();
 		let mut batching          = None;
 		let mut batched           = None;
+		let mut synchronized      = false;
 
 		let input = tty.output();
 
@@ -225,7 +227,28 @@ fn main() {
 			(handle $what:expr) => ({
 				let (actions, touched) = try!(continue $what);
 
-				if touched.is_total() && batched.is_none() && config.environment().batch().is_some() {
+				// Hold renders while the application is synchronizing its output,
+				// until it's done or the timeout expires.
+				if interface.mode().contains(terminal::mode::SYNCHRONIZED) {
+					// A pending batch is replaced when synchronizing starts, so it can't
+					// render half a frame.
+					if !synchronized || batched.is_none() {
+						batching = Some(true);
+					}
+
+					synchronized = true;
+				}
+				else if synchronized {
+					synchronized = false;
+
+					if batched.is_some() {
+						batching = Some(false);
+					}
+					else {
+						render!(interface.region().absolute());
+					}
+				}
+				else if touched.is_total() && batched.is_none() && config.environment().batch().is_some() {
 					batching = Some(true);
 				}
 				else if batched.is_none() && !touched.is_empty() {
@@ -257,6 +280,14 @@ fn main() {
 							window.resize(width, height);
 						}
 
+						Action::Iconify(value) => {
+							window.iconify(value);
+						}
+
+						Action::Raise => {
+							window.raise();
+						}
+
 						Action::Copy(name, value) => {
 							window.copy(name, value);
 						}
@@ -266,7 +297,9 @@ fn main() {
 						}
 
 						Action::Open(through, what) => {
-							window.open(through.as_ref().map(AsRef::as_ref), what.as_ref()).unwrap();
+							if let Err(err) = window.open(through.as_ref().map(AsRef::as_ref), what.as_ref(), interface.cwd()) {
+								error!(target: "cancer::open", "could not open {:?}: {}", what, err);
+							}
 						}
 					}
 				}
@@ -290,8 +323,19 @@ fn main() {
 			loop {
 				match batching.take() {
 					Some(true) => {
-						batched = Some(mem::replace(&mut batch,
-							timer::oneshot_ms(config.environment().batch().unwrap())));
+						let timeout = if synchronized {
+							config.environment().synchronize()
+						}
+						else {
+							config.environment().batch().unwrap()
+						};
+
+						if batched.is_some() {
+							batch = timer::oneshot_ms(timeout);
+						}
+						else {
+							batched = Some(mem::replace(&mut batch, timer::oneshot_ms(timeout)));
+						}
 					}
 
 					Some(false) => {
@@ -385,9 +429,11 @@ fn main() {
 
 							Event::Mouse(mut event) => {
 								match event {
-									Mouse::Click(mouse::Click { ref mut position, .. }) |
-									Mouse::Motion(mouse::Motion { ref mut position, .. }) => {
+									Mouse::Click(mouse::Click { ref mut position, ref mut pixel, .. }) |
+									Mouse::Motion(mouse::Motion { ref mut position, ref mut pixel, .. }) => {
 										if let Some((x, y)) = renderer.position(position.x, position.y) {
+											pixel.x    = position.x.saturating_sub(renderer.margin().horizontal);
+											pixel.y    = position.y.saturating_sub(renderer.margin().vertical);
 											position.x = x;
 											position.y = y;
 										}
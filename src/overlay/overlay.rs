@@ -19,6 +19,7 @@ use std::rc::Rc;
 use std::io::Write;
 use std::ops::{Index, Deref, DerefMut};
 use std::vec;
+use std::cmp;
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
@@ -30,7 +31,9 @@ use style::{self, Style};
 use platform::Clipboard;
 use platform::key::{self, Key};
 use platform::mouse::{self, Mouse};
-use terminal::{Terminal, Cursor, Iter, Row};
+use terminal::{Terminal, Cursor, Iter, Row, Link};
+use terminal::graphics::Placement;
+use terminal::row;
 use terminal::touched::{self, Touched};
 use terminal::cell::{self, Cell};
 use terminal::cursor;
@@ -180,9 +183,15 @@ impl Overlay {
 			status.mode("NORMAL");
 
 			let (x, y) = cursor.position();
-			let y      = inner.grid().back().len() as u32 + y + 2;
+			let y      = if inner.alternate() { 0 } else { inner.grid().back().len() as u32 } + y + 2;
 			status.position((x, y));
 
+			if inner.config().overlay().directory() {
+				if let Some(path) = inner.cwd() {
+					status.directory(path.to_string_lossy());
+				}
+			}
+
 			status
 		});
 
@@ -238,10 +247,59 @@ impl Overlay {
 		Iter::new(self, iter)
 	}
 
+	/// Get the line size of the given row.
+	pub fn size(&self, y: u32) -> row::Size {
+		// The status bar is always a single line.
+		if self.status.is_some() && y == self.inner.rows() - 1 {
+			return row::Size::Single;
+		}
+
+		let offset = (self.inner.rows() - 1 - y) + self.scroll -
+			if self.status.is_some() { 1 } else { 0 };
+
+		self[offset].size()
+	}
+
+	/// Get the images reaching into the view, with the row they're anchored
+	/// to.
+	pub fn images(&self) -> Vec<(i32, Rc<Placement>)> {
+		let rows  = self.inner.rows() - if self.status.is_some() { 1 } else { 0 };
+		let total = self.history() + self.inner.grid().view().len() as u32;
+
+		let mut images = Vec::new();
+
+		// Rows below the view can't reach into it, rows above can if they're close
+		// enough.
+		let end = cmp::min(total, self.scroll.saturating_add(rows).saturating_add(self.inner.grid().reach()));
+
+		for offset in self.scroll .. end {
+			let y = rows as i32 - 1 - (offset - self.scroll) as i32;
+
+			for placement in self[offset].images() {
+				if y + placement.rows() as i32 > 0 {
+					images.push((y, placement.clone()));
+				}
+			}
+		}
+
+		images
+	}
+
+	/// Get the number of scroll back rows reachable from the overlay, the
+	/// alternate screen has none.
+	fn history(&self) -> u32 {
+		if self.inner.alternate() {
+			0
+		}
+		else {
+			self.inner.grid().back().len() as u32
+		}
+	}
+
 	/// Check if the cursor is at the beginning.
 	fn at_start(&self) -> bool {
 		let (x, y) = overlay!(self; cursor);
-		let back   = self.inner.grid().back().len() as u32 +
+		let back   = self.history() +
 			if self.status.is_some() { 1 } else { 0 };
 
 		self.scroll == back &&
@@ -258,6 +316,85 @@ impl Overlay {
 		y == self.inner.rows() - 1 - if self.status.is_some() { 1 } else { 0 }
 	}
 
+	/// Move the cursor to the start of the given absolute row, scrolling if
+	/// needed.
+	fn jump(&mut self, y: u32) {
+		let offset  = if self.status.is_some() { 1 } else { 0 };
+		let visible = self.inner.rows() - offset;
+
+		if y < self.scroll {
+			self.scroll = y;
+		}
+		else if y > self.scroll + visible - 1 {
+			self.scroll = cmp::min(y - (visible - 1),
+				self.history() + offset);
+		}
+
+		let row = self.inner.rows() - 1 - (y - self.scroll + offset);
+		overlay!(self; cursor Position(Some(0), Some(row)));
+
+		self.touched.all();
+	}
+
+	/// Find the absolute rows spanning the output of the command under the
+	/// cursor, or following the prompt under the cursor.
+	fn output(&self) -> Option<(u32, u32)> {
+		let (_, y) = overlay!(self; cursor absolute);
+		let total  = self.history() + self.inner.grid().view().len() as u32;
+
+		// Look up for the start of the output, unless the cursor is on a prompt.
+		let mut start = None;
+		for y in y .. total {
+			let mark = self[y].mark();
+
+			if mark.contains(row::OUTPUT) {
+				start = Some(y);
+				break;
+			}
+
+			if mark.intersects(row::PROMPT | row::INPUT) {
+				break;
+			}
+		}
+
+		// Look down for the start of the output of the command in the prompt.
+		if start.is_none() {
+			for y in (0 .. y).rev() {
+				let mark = self[y].mark();
+
+				if mark.contains(row::OUTPUT) {
+					start = Some(y);
+					break;
+				}
+
+				if mark.contains(row::PROMPT) {
+					break;
+				}
+			}
+		}
+
+		let start = try!(option start);
+
+		// The output ends right before the next prompt, or where the terminal
+		// cursor is if the command is still running.
+		let mut end = cmp::min(start,
+			self.inner.grid().view().len() as u32 - 1 - self.inner.cursor().y());
+
+		for y in (0 .. start).rev() {
+			if self[y].mark().intersects(row::PROMPT | row::END) {
+				end = y + 1;
+				break;
+			}
+		}
+
+		if end > start {
+			None
+		}
+		else {
+			Some((start, end))
+		}
+	}
+
 	/// Handle key input.
 	pub fn key(&mut self, key: Key) -> (vec::IntoIter<Action>, touched::Iter) {
 		use platform::key::{Value, Button, Keypad};
@@ -327,6 +464,12 @@ impl Overlay {
 				"g" if key.modifier().is_empty() && prefix == Some(b'g') =>
 					Command::Scroll(command::Scroll::Begin),
 
+				"[" if prefix == Some(b'[') =>
+					Command::Move(command::Move::Previous(times.unwrap_or(1), command::Previous::Prompt)),
+
+				"]" if prefix == Some(b']') =>
+					Command::Move(command::Move::Next(times.unwrap_or(1), command::Next::Prompt)),
+
 				ch if prefix == Some(b'f') =>
 					Command::Move(command::Move::Next(times.unwrap_or(1),
 						command::Next::Match(command::Match::After(ch.into())))),
@@ -428,6 +571,9 @@ impl Overlay {
 				"V" if key.modifier() == key::SHIFT =>
 					Command::Select(command::Select::Line),
 
+				"O" if key.modifier() == key::SHIFT =>
+					Command::Select(command::Select::Output),
+
 				"y" if key.modifier().is_empty() =>
 					Command::Copy(match times {
 						Some(1) => Clipboard::Primary,
@@ -468,6 +614,16 @@ impl Overlay {
 					Command::None
 				}
 
+				"[" => {
+					self.prefix = Some(b'[');
+					Command::None
+				}
+
+				"]" => {
+					self.prefix = Some(b']');
+					Command::None
+				}
+
 				_ => {
 					debug!(target: "cancer::overlay::unhandled", "key {:?}", key);
 					Command::None
@@ -594,12 +750,14 @@ impl Overlay {
 
 	/// Handle a command.
 	fn handle(&mut self, command: Command) -> Vec<Action> {
-		let     before  = overlay!(self; cursor absolute);
-		let mut actions = self.command(command);
-		let     after   = overlay!(self; cursor absolute);
+		let     before   = overlay!(self; cursor absolute);
+		let     selected = self.selector.current;
+		let mut actions  = self.command(command);
+		let     after    = overlay!(self; cursor absolute);
 
 		if after != before {
-			if self.selector.current.is_some() {
+			// Only extend the selection if the command didn't replace it.
+			if self.selector.current.is_some() && self.selector.current == selected {
 				let s = self.selector.current.unwrap();
 				self.highlight(Highlight::Selection(&s), false);
 				self.select(before, after);
@@ -610,7 +768,7 @@ impl Overlay {
 
 			if let Some(status) = self.status.as_mut() {
 				let x = after.0 + 1;
-				let y = self.inner.grid().back().len() as u32 + self.inner.grid().view().len() as u32 - after.1;
+				let y = self.history() + self.inner.grid().view().len() as u32 - after.1;
 
 				self.touched.line(self.inner.rows() - 1);
 				status.position((x, y));
@@ -657,7 +815,7 @@ impl Overlay {
 
 			// Scrolling commands.
 			Command::Scroll(command::Scroll::Begin) => {
-				self.scroll = self.inner.grid().back().len() as u32
+				self.scroll = self.history()
 					+ if self.status.is_some() { 1 } else { 0 };
 
 				self.touched.all();
@@ -670,7 +828,7 @@ impl Overlay {
 			}
 
 			Command::Scroll(command::Scroll::To(n)) => {
-				self.scroll = (self.inner.grid().back().len() as u32).saturating_sub(n - 1);
+				self.scroll = (self.history()).saturating_sub(n - 1);
 
 				if self.status.is_some() {
 					self.scroll += 1;
@@ -683,7 +841,7 @@ impl Overlay {
 				for _ in 0 .. times {
 					let offset = if self.status.is_some() { 1 } else { 0 };
 
-					if self.scroll < self.inner.grid().back().len() as u32 + offset {
+					if self.scroll < self.history() + offset {
 						self.scroll += 1;
 					}
 				}
@@ -705,8 +863,8 @@ impl Overlay {
 				for _ in 0 .. times {
 					self.scroll += self.inner.rows().saturating_sub(3);
 
-					if self.scroll > self.inner.grid().back().len() as u32 {
-						self.scroll = self.inner.grid().back().len().saturating_sub(1) as u32;
+					if self.scroll > self.history() {
+						self.scroll = self.history().saturating_sub(1);
 					}
 				}
 
@@ -940,7 +1098,48 @@ impl Overlay {
 				}
 			}
 
+			Command::Move(command::Move::Previous(times, command::Previous::Prompt)) => {
+				let (_, mut y) = overlay!(self; cursor absolute);
+				let     total  = self.history() + self.inner.grid().view().len() as u32;
+
+				for _ in 0 .. times {
+					if let Some(prompt) = (y + 1 .. total).find(|&y| self[y].mark().contains(row::PROMPT)) {
+						y = prompt;
+					}
+				}
+
+				self.jump(y);
+			}
+
+			Command::Move(command::Move::Next(times, command::Next::Prompt)) => {
+				let (_, mut y) = overlay!(self; cursor absolute);
+
+				for _ in 0 .. times {
+					if let Some(prompt) = (0 .. y).rev().find(|&y| self[y].mark().contains(row::PROMPT)) {
+						y = prompt;
+					}
+				}
+
+				self.jump(y);
+			}
+
 			// Selection commands.
+			Command::Select(command::Select::Output) => {
+				if let Some((start, end)) = self.output() {
+					let selection = Selection::Line { start: start, end: end };
+
+					if let Some(old) = self.selector.current.take() {
+						self.highlight(Highlight::Selection(&old), false);
+					}
+
+					overlay!(self; status mode "VISUAL LINE");
+
+					self.selector.current = Some(selection);
+					self.highlight(Highlight::Selection(&selection), true);
+					self.jump(start);
+				}
+			}
+
 			Command::Select(mode) => {
 				let (name, old, new) = match (mode, self.selector.current.take()) {
 					(command::Select::Normal, Some(Selection::Normal { start, end })) => {
@@ -1024,6 +1223,9 @@ impl Overlay {
 							None,
 							Some(Selection::Line { start: y, end: y }))
 					}
+
+					(command::Select::Output, _) =>
+						unreachable!(),
 				};
 
 				overlay!(self; status mode name);
@@ -1062,8 +1264,9 @@ impl Overlay {
 				let config  = self.inner.config().overlay().hinter(id).clone();
 				let content = self.selection(&Selection::Line { start: top, end: bottom });
 				let urls    = config.matcher().find_iter(&content).collect::<Vec<_>>();
+				let links   = self.links(top, bottom);
 
-				if !urls.is_empty() {
+				if !urls.is_empty() || !links.is_empty() {
 					overlay!(self; status mode "HINT");
 
 					self.hinter.label  = Rc::new(*config.style());
@@ -1071,15 +1274,21 @@ impl Overlay {
 						foreground: config.style().foreground,
 						background: config.style().background,
 						attributes: config.style().attributes ^ style::REVERSE,
+						underline:  config.style().underline,
 					});
 
-					self.hinter.hints  = Some(Hints::new(config.label().to_vec(), urls.len()));
+					self.hinter.hints  = Some(Hints::new(config.label().to_vec(), urls.len() + links.len()));
 					self.hinter.config = config;
 
 					for url in urls {
 						self.hint(url, &content);
 					}
 
+					for (position, uri) in links {
+						let hint = self.hinter.hints.as_mut().unwrap().put(position, uri).clone();
+						self.highlight(Highlight::Hint(&hint, 0), true);
+					}
+
 					self.touched.all();
 				}
 				else {
@@ -1380,6 +1589,43 @@ impl Overlay {
 		}
 	}
 
+	/// Find the explicit hyperlinks between the given rows.
+	fn links(&self, top: u32, bottom: u32) -> Vec<(((u32, u32), (u32, u32)), String)> {
+		let mut links   = Vec::new();
+		let mut current = None::<((u32, u32), Rc<Link>)>;
+
+		for y in (bottom ... top).rev() {
+			for x in 0 .. self.inner.columns() {
+				let cell = &self[y][x as usize];
+
+				if cell.is_reference() {
+					continue;
+				}
+
+				if let Some(&(_, ref link)) = current.as_ref() {
+					if Link::same(Some(link), cell.link()) {
+						continue;
+					}
+				}
+
+				if let Some((start, link)) = current.take() {
+					links.push(((start, (x, y)), link.uri().to_owned()));
+				}
+
+				if let Some(link) = cell.link() {
+					current = Some(((x, y), link.clone()));
+				}
+			}
+		}
+
+		// The link goes to the end of the input.
+		if let Some((start, link)) = current.take() {
+			links.push(((start, (0, bottom.wrapping_sub(1))), link.uri().to_owned()));
+		}
+
+		links
+	}
+
 	/// Mark cells in a terminal as a hint.
 	fn hint<T: AsRef<str>>(&mut self, (start, end): (usize, usize), content: T) {
 		let content = content.as_ref();
@@ -1514,7 +1760,8 @@ impl Overlay {
 					}
 				}
 
-				// Make the URL underscored or selected.
+				// Make the URL underscored or selected, the end can be right past the
+				// last row.
 				while (x, y) != hint.position.1 {
 					if flag {
 						let mut cell = self[y][x as usize].clone();
@@ -1528,7 +1775,7 @@ impl Overlay {
 					x += 1;
 					if x >= self.inner.columns() {
 						x  = 0;
-						y -= 1;
+						y  = y.wrapping_sub(1);
 					}
 				}
 			}
@@ -28,9 +28,10 @@ pub struct Status {
 	cols:  u32,
 	style: Rc<Style>,
 
-	inner:    Vec<Cell>,
-	mode:     String,
-	position: String,
+	inner:     Vec<Cell>,
+	mode:      String,
+	directory: String,
+	position:  String,
 }
 
 impl Status {
@@ -42,40 +43,58 @@ impl Status {
 			cols:  cols,
 			style: style.clone(),
 
-			inner:    vec![Cell::empty(style.clone()); cols as usize],
-			mode:     "".into(),
-			position: "".into(),
+			inner:     vec![Cell::empty(style.clone()); cols as usize],
+			mode:      "".into(),
+			directory: "".into(),
+			position:  "".into(),
 		}
 	}
 
 	/// Change the mode shown in the status bar.
 	pub fn mode<T: Into<String>>(&mut self, string: T) {
-		let string = string.into();
-
-		for (_, cell) in self.mode.graphemes(true).zip(self.inner.iter_mut()) {
-			cell.make_empty(self.style.clone());
-		}
-
-		for (ch, cell) in string.graphemes(true).zip(self.inner.iter_mut()) {
-			cell.make_occupied(ch, self.style.clone());
-		}
+		self.mode = string.into();
+		self.update();
+	}
 
-		self.mode = string;
+	/// Change the working directory shown in the status bar.
+	pub fn directory<T: Into<String>>(&mut self, string: T) {
+		self.directory = string.into();
+		self.update();
 	}
 
 	/// Change the cursor position shown in the status bar.
 	pub fn position(&mut self, (x, y): (u32, u32)) {
-		let format = format!("{}:{}", y, x);
+		self.position = format!("{}:{}", y, x);
+		self.update();
+	}
 
-		for (_, cell) in self.position.graphemes(true).rev().zip(self.inner.iter_mut().rev()) {
+	/// Redraw the status bar contents.
+	fn update(&mut self) {
+		for cell in &mut self.inner {
 			cell.make_empty(self.style.clone());
 		}
 
-		for (ch, cell) in format.graphemes(true).rev().zip(self.inner.iter_mut().rev()) {
+		let mode     = self.mode.graphemes(true).count();
+		let position = self.position.graphemes(true).count();
+
+		for (ch, cell) in self.mode.graphemes(true).zip(self.inner.iter_mut()) {
 			cell.make_occupied(ch, self.style.clone());
 		}
 
-		self.position = format;
+		for (ch, cell) in self.position.graphemes(true).rev().zip(self.inner.iter_mut().rev()) {
+			cell.make_occupied(ch, self.style.clone());
+		}
+
+		// The directory goes right before the position, and gets truncated from
+		// the left if there isn't enough space.
+		if !self.directory.is_empty() && mode + position + 2 < self.cols as usize {
+			let available = self.cols as usize - mode - position - 2;
+			let end       = self.cols as usize - position - 1;
+
+			for (ch, cell) in self.directory.graphemes(true).rev().take(available).zip(self.inner[.. end].iter_mut().rev()) {
+				cell.make_occupied(ch, self.style.clone());
+			}
+		}
 	}
 }
 
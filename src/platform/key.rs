@@ -20,12 +20,13 @@ pub struct Key {
 	value:    Value,
 	modifier: Modifier,
 	lock:     Lock,
+	state:    State,
 }
 
 /// Implementation to ignore locks, they're just informational.
 impl PartialEq for Key {
 	fn eq(&self, other: &Key) -> bool {
-		self.modifier == other.modifier && self.value == other.value
+		self.modifier == other.modifier && self.value == other.value && self.state == other.state
 	}
 }
 
@@ -57,6 +58,19 @@ impl Default for Lock {
 	}
 }
 
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum State {
+	Press,
+	Repeat,
+	Release,
+}
+
+impl Default for State {
+	fn default() -> Self {
+		State::Press
+	}
+}
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum Value {
 	Char(String),
@@ -141,11 +155,12 @@ impl From<Keypad> for Value {
 }
 
 impl Key {
-	pub fn new(value: Value, modifier: Modifier, lock: Lock) -> Self {
+	pub fn new(value: Value, modifier: Modifier, lock: Lock, state: State) -> Self {
 		Key {
 			value:    value,
 			modifier: modifier,
 			lock:     lock,
+			state:    state,
 		}
 	}
 
@@ -163,4 +178,9 @@ impl Key {
 	pub fn lock(&self) -> Lock {
 		self.lock
 	}
+
+	/// Get whether the key was pressed, repeated or released.
+	pub fn state(&self) -> State {
+		self.state
+	}
 }
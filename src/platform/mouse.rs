@@ -29,12 +29,14 @@ pub struct Click {
 	pub modifier: key::Modifier,
 	pub button:   Button,
 	pub position: Position,
+	pub pixel:    Position,
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct Motion {
 	pub modifier: key::Modifier,
 	pub position: Position,
+	pub pixel:    Position,
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -44,6 +46,9 @@ pub enum Button {
 	Right,
 	Up,
 	Down,
+	Back,
+	Forward,
+	Other(u8),
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::mpsc::Sender;
+use std::path::Path;
 
 use sys::cairo;
 use error;
@@ -38,6 +39,12 @@ pub trait Proxy: Send {
 	/// Set the window title.
 	fn set_title(&self, title: String) { }
 
+	/// Iconify or restore the window.
+	fn iconify(&self, value: bool) { }
+
+	/// Raise the window to the top of the stack.
+	fn raise(&self) { }
+
 	/// Change the clipboard contents.
 	fn copy(&self, name: Clipboard, value: String) { }
 
@@ -52,6 +59,6 @@ pub trait Proxy: Send {
 		f(); surface.flush();
 	}
 
-	/// Open the given item.
-	fn open(&self, through: Option<&str>, value: &str) -> error::Result<()> { Ok(()) }
+	/// Open the given item, optionally from the given directory.
+	fn open(&self, through: Option<&str>, value: &str, cwd: Option<&Path>) -> error::Result<()> { Ok(()) }
 }
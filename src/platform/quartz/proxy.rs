@@ -17,6 +17,7 @@
 
 use std::sync::Arc;
 use std::process::Command;
+use std::path::Path;
 use std::cell::RefCell;
 use std::ffi::CStr;
 use std::str;
@@ -83,6 +84,23 @@ impl platform::Proxy for Proxy {
 		}
 	}
 
+	fn iconify(&self, value: bool) {
+		unsafe {
+			if value {
+				msg_send![*self.window, miniaturize:nil];
+			}
+			else {
+				msg_send![*self.window, deminiaturize:nil];
+			}
+		}
+	}
+
+	fn raise(&self) {
+		unsafe {
+			msg_send![*self.window, orderFront:nil];
+		}
+	}
+
 	fn copy(&self, _name: Clipboard, value: String) {
 		unsafe {
 			let paste = NSPasteboard::generalPasteboard(nil);
@@ -97,12 +115,16 @@ impl platform::Proxy for Proxy {
 				let paste = NSPasteboard::generalPasteboard(nil);
 				let value = paste.stringForType(NSPasteboardTypeString);
 
+				// Every paste request gets an answer, even if empty.
 				if value != nil {
 					let string = value.UTF8String();
 					let string = CStr::from_ptr(string);
 					let string = str::from_utf8_unchecked(string.to_bytes());
 					let _      = manager.send(Event::Paste(string.into()));
 				}
+				else {
+					let _ = manager.send(Event::Paste(Vec::new()));
+				}
 			}
 		}
 	}
@@ -123,8 +145,15 @@ impl platform::Proxy for Proxy {
 		}
 	}
 
-	fn open(&self, through: Option<&str>, value: &str) -> error::Result<()> {
-		Command::new(through.unwrap_or("open")).arg(value).spawn()?;
+	fn open(&self, through: Option<&str>, value: &str, cwd: Option<&Path>) -> error::Result<()> {
+		let mut command = Command::new(through.unwrap_or("open"));
+		command.arg(value);
+
+		if let Some(cwd) = cwd {
+			command.current_dir(cwd);
+		}
+
+		command.spawn()?;
 
 		Ok(())
 	}
@@ -112,7 +112,7 @@ impl Window {
 			}
 		}
 
-		unsafe fn key(event: *mut Object, modifier: key::Modifier, lock: key::Lock) -> Option<Key> {
+		unsafe fn key(event: *mut Object, modifier: key::Modifier, lock: key::Lock, state: key::State) -> Option<Key> {
 			use platform::key::{Button, Keypad};
 
 			Some(Key::new(match event.keyCode() {
@@ -178,7 +178,7 @@ impl Window {
 
 					string.to_owned().into()
 				}
-			}, modifier, lock))
+			}, modifier, lock, state))
 		}
 
 		unsafe {
@@ -258,7 +258,20 @@ impl Window {
 
 					// Handle key input.
 					appkit::NSKeyDown => {
-						if let Some(key) = key(event, modifier, lock) {
+						let state = if event.isARepeat() == YES {
+							key::State::Repeat
+						}
+						else {
+							key::State::Press
+						};
+
+						if let Some(key) = key(event, modifier, lock, state) {
+							try!(manager.send(Event::Key(key)));
+						}
+					}
+
+					appkit::NSKeyUp => {
+						if let Some(key) = key(event, modifier, lock, key::State::Release) {
 							try!(manager.send(Event::Key(key)));
 						}
 					}
@@ -278,15 +291,20 @@ impl Window {
 							0 => Some(mouse::Button::Left),
 							1 => Some(mouse::Button::Right),
 							2 => Some(mouse::Button::Middle),
+							3 => Some(mouse::Button::Back),
+							4 => Some(mouse::Button::Forward),
 							_ => None
 						};
 
 						if let Some(button) = button {
+							let position = position(&self.window, &self.view, event);
+
 							try!(manager.send(Event::Mouse(Mouse::Click(mouse::Click {
 								press:    press,
 								button:   button,
 								modifier: modifier,
-								position: position(&self.window, &self.view, event),
+								position: position,
+								pixel:    position,
 							}))));
 						}
 					}
@@ -296,9 +314,12 @@ impl Window {
 					appkit::NSLeftMouseDragged |
 					appkit::NSRightMouseDragged |
 					appkit::NSOtherMouseDragged => {
+						let position = position(&self.window, &self.view, event);
+
 						try!(manager.send(Event::Mouse(Mouse::Motion(mouse::Motion {
 							modifier: modifier,
-							position: position(&self.window, &self.view, event),
+							position: position,
+							pixel:    position,
 						}))));
 					}
 
@@ -310,11 +331,14 @@ impl Window {
 							mouse::Button::Down
 						};
 
+						let position = position(&self.window, &self.view, event);
+
 						try!(manager.send(Event::Mouse(Mouse::Click(mouse::Click {
 							press:    true,
 							button:   button,
 							modifier: modifier,
-							position: position(&self.window, &self.view, event),
+							position: position,
+							pixel:    position,
 						}))));
 					}
 
@@ -17,13 +17,14 @@
 
 use std::sync::Arc;
 use std::env;
+use std::collections::HashSet;
 
 use xcb;
 use xcbu::ewmh;
 use xkb;
 
 use error;
-use platform::key::{self, Key, Button, Keypad, Modifier, Lock};
+use platform::key::{self, Key, Button, Keypad, Modifier, Lock, State};
 
 pub struct Keyboard {
 	connection: Arc<ewmh::Connection>,
@@ -35,6 +36,7 @@ pub struct Keyboard {
 	#[allow(dead_code)]
 	table:   xkb::compose::Table,
 	compose: xkb::compose::State,
+	pressed: HashSet<u8>,
 }
 
 unsafe impl Send for Keyboard { }
@@ -79,6 +81,14 @@ impl Keyboard {
 				map as u16, map as u16, None).request_check()?;
 		}
 
+		// Make auto-repeat detectable, so repeated keys aren't sent as a release
+		// followed by a press.
+		xcb::xkb::per_client_flags(&connection,
+			xcb::xkb::ID_USE_CORE_KBD as u16,
+			xcb::xkb::PER_CLIENT_FLAG_DETECTABLE_AUTO_REPEAT,
+			xcb::xkb::PER_CLIENT_FLAG_DETECTABLE_AUTO_REPEAT,
+			0, 0, 0).get_reply()?;
+
 		let context = xkb::Context::default();
 		let device  = xkb::x11::device(&connection)?;
 		let keymap  = xkb::x11::keymap(&connection, device, &context, Default::default())?;
@@ -107,6 +117,7 @@ impl Keyboard {
 
 			table:   table,
 			compose: compose,
+			pressed: HashSet::new(),
 		})
 	}
 
@@ -155,7 +166,20 @@ impl Keyboard {
 		self.state.key(code).utf8()
 	}
 
-	pub fn key(&mut self, code: u8) -> Option<Key> {
+	/// Translate a key press or release to a key.
+	pub fn key(&mut self, code: u8, press: bool) -> Option<Key> {
+		// A press of a key that is already down is a repeat.
+		let state = if !press {
+			self.pressed.remove(&code);
+			State::Release
+		}
+		else if !self.pressed.insert(code) {
+			State::Repeat
+		}
+		else {
+			State::Press
+		};
+
 		let modifier = [
 			(xkb::name::mods::ALT,   key::ALT),
 			(xkb::name::mods::CTRL,  key::CTRL),
@@ -181,7 +205,11 @@ impl Keyboard {
 			});
 
 		let symbol = try!(option self.symbol(code));
-		self.compose.feed(symbol);
+
+		// Releases don't take part in composition.
+		if state != State::Release {
+			self.compose.feed(symbol);
+		}
 
 		debug!(target: "cancer::platform::key", "compose status: {:?}", self.compose.status());
 
@@ -193,7 +221,7 @@ impl Keyboard {
 			xkb::compose::Status::Composed => {
 				if let Some(string) = self.compose.utf8() {
 					self.compose.reset();
-					return Some(Key::new(string.into(), modifier, lock));
+					return Some(Key::new(string.into(), modifier, lock, state));
 				}
 			}
 
@@ -363,6 +391,6 @@ impl Keyboard {
 
 				string.into()
 			}
-		}, modifier, lock))
+		}, modifier, lock, state))
 	}
 }
@@ -18,6 +18,7 @@
 use std::sync::Arc;
 use std::sync::mpsc::Sender;
 use std::process::Command;
+use std::path::Path;
 
 use xcb;
 use xcbu::ewmh;
@@ -65,6 +66,43 @@ impl platform::Proxy for Proxy {
 		self.request.send(Request::Title(title)).unwrap();
 	}
 
+	fn iconify(&self, value: bool) {
+		if value {
+			let root = if let Some(screen) = self.connection.get_setup().roots().nth(self.screen as usize) {
+				screen.root()
+			}
+			else {
+				return;
+			};
+
+			let change = if let Ok(reply) = xcb::intern_atom(&self.connection, false, "WM_CHANGE_STATE").get_reply() {
+				reply.atom()
+			}
+			else {
+				return;
+			};
+
+			// Ask the window manager to move the window to the `IconicState`.
+			let event = xcb::ClientMessageEvent::new(32, self.window, change,
+				xcb::ClientMessageData::from_data32([3, 0, 0, 0, 0]));
+
+			xcb::send_event(&self.connection, false, root,
+				xcb::EVENT_MASK_SUBSTRUCTURE_REDIRECT | xcb::EVENT_MASK_SUBSTRUCTURE_NOTIFY, &event);
+		}
+		else {
+			xcb::map_window(&self.connection, self.window);
+		}
+
+		self.connection.flush();
+	}
+
+	fn raise(&self) {
+		xcb::configure_window(&self.connection, self.window, &[
+			(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_ABOVE)]);
+
+		self.connection.flush();
+	}
+
 	fn copy(&self, name: Clipboard, value: String) {
 		self.request.send(Request::Copy(name, value)).unwrap();
 	}
@@ -83,8 +121,15 @@ impl platform::Proxy for Proxy {
 		self.request.send(Request::Flush).unwrap();
 	}
 
-	fn open(&self, through: Option<&str>, value: &str) -> error::Result<()> {
-		Command::new(through.unwrap_or("xdg-open")).arg(value).spawn()?;
+	fn open(&self, through: Option<&str>, value: &str, cwd: Option<&Path>) -> error::Result<()> {
+		let mut command = Command::new(through.unwrap_or("xdg-open"));
+		command.arg(value);
+
+		if let Some(cwd) = cwd {
+			command.current_dir(cwd);
+		}
+
+		command.spawn()?;
 
 		Ok(())
 	}
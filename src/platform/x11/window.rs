@@ -288,16 +288,24 @@ impl Window {
 							self.connection.flush();
 						}
 
+						// Every paste request gets an answer, so they can be told apart by
+						// their order.
+						xcb::SELECTION_NOTIFY => {
+							let event = xcb::cast_event::<xcb::SelectionNotifyEvent>(&event);
+
+							if event.property() == xcb::NONE {
+								try!(manager.send(Event::Paste(Vec::new())));
+							}
+						}
+
 						xcb::PROPERTY_NOTIFY => {
 							let event = xcb::cast_event::<xcb::PropertyNotifyEvent>(&event);
 
-							if event.atom() == SELECTION {
-								let reply = try!(continue icccm::get_text_property(&self.connection, self.window, SELECTION).get_reply());
+							if event.atom() == SELECTION && event.state() as u32 == xcb::PROPERTY_NEW_VALUE {
+								let reply = icccm::get_text_property(&self.connection, self.window, SELECTION).get_reply();
 								xcb::delete_property(&self.connection, self.window, SELECTION);
 
-								if !reply.name().is_empty() {
-									try!(manager.send(Event::Paste(reply.name().as_bytes().to_vec())));
-								}
+								try!(manager.send(Event::Paste(reply.map(|r| r.name().as_bytes().to_vec()).unwrap_or(Vec::new()))));
 							}
 						}
 
@@ -311,6 +319,9 @@ impl Window {
 								3 => mouse::Button::Right,
 								4 => mouse::Button::Up,
 								5 => mouse::Button::Down,
+								8 => mouse::Button::Back,
+								9 => mouse::Button::Forward,
+								n @ 10 ... 11 => mouse::Button::Other(n),
 								_ => continue,
 							};
 
@@ -318,26 +329,32 @@ impl Window {
 								continue;
 							}
 
+							let position = mouse::Position {
+								x: event.event_x() as u32,
+								y: event.event_y() as u32,
+							};
+
 							try!(manager.send(Event::Mouse(Mouse::Click(mouse::Click {
 								press:    press,
 								button:   button,
 								modifier: key::Modifier::from(event.state()),
-								position: mouse::Position {
-									x: event.event_x() as u32,
-									y: event.event_y() as u32,
-								}
+								position: position,
+								pixel:    position,
 							}))));
 						}
 
 						xcb::MOTION_NOTIFY => {
 							let event = xcb::cast_event::<xcb::MotionNotifyEvent>(&event);
 
+							let position = mouse::Position {
+								x: event.event_x() as u32,
+								y: event.event_y() as u32,
+							};
+
 							try!(manager.send(Event::Mouse(Mouse::Motion(mouse::Motion {
 								modifier: key::Modifier::from(event.state()),
-								position: mouse::Position {
-									x: event.event_x() as u32,
-									y: event.event_y() as u32,
-								}
+								position: position,
+								pixel:    position,
 							}))));
 						}
 
@@ -345,10 +362,11 @@ impl Window {
 							self.keyboard.handle(&event);
 						}
 
-						xcb::KEY_PRESS => {
+						xcb::KEY_PRESS | xcb::KEY_RELEASE => {
+							let press = event.response_type() == xcb::KEY_PRESS;
 							let event = xcb::cast_event::<xcb::KeyPressEvent>(&event);
 
-							if let Some(key) = self.keyboard.key(event.detail()) {
+							if let Some(key) = self.keyboard.key(event.detail(), press) {
 								try!(manager.send(Event::Key(key)));
 							}
 						}
@@ -18,7 +18,7 @@
 use std::rc::Rc;
 
 use style::{self, Style};
-use terminal::cell;
+use terminal::{cell, row};
 use renderer::Options;
 
 /// Cache for cells to avoid rendering a cell multiple times when it's not
@@ -35,6 +35,7 @@ pub struct Cache {
 pub struct Cell {
 	style: Rc<Style>,
 	value: Option<String>,
+	size:  Option<row::Size>,
 	flags: Flags,
 }
 
@@ -44,6 +45,7 @@ impl Cell {
 		Cell {
 			style: style,
 			value: None,
+			size:  None,
 			flags: Flags::empty(),
 		}
 	}
@@ -98,9 +100,9 @@ impl Cache {
 
 	/// Update the cache, returns `false` if the cache is valid.
 	///
-	/// The cell is seen as unchanged if it's valid, the style and content match
-	/// and the rendering options match.
-	pub fn update(&mut self, cell: &cell::Position, options: Options) -> bool {
+	/// The cell is seen as unchanged if it's valid, the style, content and line
+	/// size match and the rendering options match.
+	pub fn update(&mut self, cell: &cell::Position, size: row::Size, options: Options) -> bool {
 		debug_assert!(!cell.is_reference());
 
 		let index = (cell.y() * self.width + cell.x()) as usize;
@@ -114,6 +116,7 @@ impl Cache {
 			   (!cache.style.attributes().contains(style::BLINK) ||
 				   cache.flags.contains(BLINKING) == options.blinking()) &&
 			   cell.style() == &cache.style &&
+			   cache.size == Some(size) &&
 			   ((cell.is_empty() && cache.value.is_none()) ||
 			    (cell.is_occupied() && cache.value.as_ref().map(AsRef::as_ref) == Some(cell.value())))
 			{
@@ -125,6 +128,7 @@ impl Cache {
 		self.inner[index] = Cell {
 			style: cell.style().clone(),
 			value: if cell.is_empty() { None } else { Some(cell.value().into()) },
+			size:  Some(size),
 			flags: VALID
 				| if options.blinking() { BLINKING } else { NONE }
 				| if options.reverse() { REVERSE } else { NONE }
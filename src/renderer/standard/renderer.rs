@@ -16,15 +16,19 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
-use std::rc::Rc;
+use std::f64;
+use std::rc::{Rc, Weak};
 
 use picto::Region;
+use picto::color::Rgba;
 use config::style::Shape;
 use sys::cairo;
 use style;
-use terminal::{cell, cursor};
+use terminal::{cell, cursor, row, Palette, Link};
+use terminal::graphics::Placement;
 use interface::Interface;
 use renderer::{State, Options};
+use renderer::option;
 use renderer::standard::{Cache, Glyphs};
 
 /// Renderer for a `cairo::Surface`.
@@ -32,6 +36,10 @@ pub struct Renderer {
 	context: cairo::Context,
 	cache:   Cache,
 	glyphs:  Glyphs,
+	colors:  Option<(Rgba<f64>, Rgba<f64>)>,
+	hover:   Option<Rc<Link>>,
+	images:  Vec<(i32, Rc<Placement>)>,
+	placed:  Vec<(i32, Weak<Placement>)>,
 }
 
 unsafe impl Send for Renderer { }
@@ -47,6 +55,10 @@ impl Renderer {
 			context: context,
 			cache:   cache,
 			glyphs:  glyphs,
+			colors:  None,
+			hover:   None,
+			images:  Vec::new(),
+			placed:  Vec::new(),
 		}
 	}
 
@@ -57,34 +69,66 @@ impl Renderer {
 	}
 
 	/// Render the given changes.
-	pub fn render<I>(&mut self, state: &State, options: Options, region: Option<Region>, interface: &Interface, iter: I)
+	pub fn render<I>(&mut self, state: &State, mut options: Options, region: Option<Region>, interface: &Interface, iter: I)
 		where I: Iterator<Item = (u32, u32)>
 	{
+		let palette = interface.palette();
+
+		// Cells using the default colors are stale if they changed.
+		let colors = (*palette.foreground(), *palette.background());
+		if self.colors != Some(colors) {
+			self.colors = Some(colors);
+			options.insert(option::DAMAGE);
+		}
+
+		// Cells of links are stale if the hovered link changed.
+		let hover = interface.hover();
+		if !Link::same(self.hover.as_ref(), hover) {
+			self.hover = hover.cloned();
+			options.insert(option::DAMAGE);
+		}
+
+		// Cells covered by images are stale if the placements changed.
+		let mut images = interface.images();
+		images.sort_by_key(|&(_, ref p)| p.z());
+
+		if !Placement::same(&self.placed, &images) {
+			options.insert(option::DAMAGE);
+		}
+
+		self.images = images;
 		self.context.push();
 
 		if let Some(region) = region {
-			self.margin(state, &region);
+			self.margin(state, palette, &region);
 		}
 
 		for cell in interface.iter(iter) {
-			self.cell(state, &cell, options);
+			self.cell(state, palette, &cell, interface.size(cell.y()), options);
 		}
 
+		let cursor = interface.cursor();
+		let size   = interface.size(cursor.cell().y());
+
 		if options.cursor() {
-			self.cursor(state, &interface.cursor(), options);
+			self.cursor(state, palette, &cursor, size, options);
 		}
 		else {
-			self.cell(state, &interface.cursor().cell(), options);
+			self.cell(state, palette, &cursor.cell(), size, options);
 		}
 
 		self.context.pop();
 		self.context.paint();
+
+		// Only weak references are kept between frames, so the image data can be
+		// dropped as soon as it's not placed anymore.
+		self.placed = self.images.drain(..).map(|(y, p)| (y, Rc::downgrade(&p))).collect();
 	}
 
 	/// Draw the margins within the given region.
-	pub fn margin(&mut self, state: &State, region: &Region) {
+	pub fn margin(&mut self, state: &State, palette: &Palette, region: &Region) {
 		let (rows, columns)    = (state.rows(), state.columns());
-		let (c, f, o, s, h, v) = (state.config(), state.font(), &mut self.context, state.config().style().spacing(), state.margin().horizontal, state.margin().vertical);
+		let (f, o, s, h, v) = (state.font(), &mut self.context, state.config().style().spacing(), state.margin().horizontal, state.margin().vertical);
 
 		// Bail out if there's no margin.
 		if h == 0 && v == 0 {
@@ -94,7 +138,7 @@ impl Renderer {
 		o.save();
 		{
 			// Set to background color.
-			o.rgba(c.style().color().background());
+			o.rgba(palette.background());
 
 			// Left margin.
 			if region.x < h {
@@ -124,7 +168,7 @@ impl Renderer {
 	}
 
 	/// Draw the cursor.
-	fn cursor(&mut self, state: &State, cursor: &cursor::Cell, options: Options) {
+	fn cursor(&mut self, state: &State, palette: &Palette, cursor: &cursor::Cell, size: row::Size, options: Options) {
 		self.cache.invalidate(&cursor.cell());
 
 		let (c, o, f) = (state.config(), &mut self.context, state.font());
@@ -133,9 +177,10 @@ impl Renderer {
 		let fg   = cursor.foreground();
 		let bg   = cursor.background();
 
-		let w = f.width() * cell.width();
+		let (sx, sy, shift) = scale(size);
+		let w = f.width() * cell.width() * sx;
 		let h = f.height() + c.style().spacing();
-		let x = state.margin().horizontal + (cell.x() * f.width());
+		let x = state.margin().horizontal + (cell.x() * f.width() * sx);
 		let y = state.margin().vertical + (cell.y() * h);
 
 		o.save();
@@ -150,13 +195,13 @@ impl Renderer {
 						o.rgba(bg);
 					}
 					else {
-						o.rgba(c.style().color().background());
+						o.rgba(palette.background());
 					}
 				}
 
 				Shape::Beam | Shape::Line => {
 					o.rgba(cell.style().background().unwrap_or(
-						c.style().color().background()));
+						palette.background()));
 				}
 			}
 
@@ -164,7 +209,10 @@ impl Renderer {
 
 			// Draw the glyph.
 			if cell.is_occupied() && !(options.blinking() && cell.style().attributes().contains(style::BLINK)) {
-				o.move_to(x as f64, (y + f.ascent()) as f64);
+				o.save();
+				o.translate(x as f64, y as f64 - (shift * h) as f64);
+				o.scale(sx as f64, sy as f64);
+				o.move_to(0.0, f.ascent() as f64);
 
 				match cursor.shape() {
 					Shape::Block => {
@@ -173,12 +221,13 @@ impl Renderer {
 
 					Shape::Beam | Shape::Line => {
 						o.rgba(cell.style().foreground().unwrap_or(
-							c.style().color().foreground()));
+							palette.foreground()));
 					}
 				}
 
 				let computed = self.glyphs.compute(Rc::new(cell.value().into()), cell.style().attributes());
 				o.glyph(computed.text(), computed.glyphs());
+				o.restore();
 			}
 
 			// Render cursors that require to be on top.
@@ -221,19 +270,35 @@ impl Renderer {
 	}
 
 	/// Draw the given cell.
-	fn cell(&mut self, state: &State, cell: &cell::Position, options: Options) -> bool {
+	fn cell(&mut self, state: &State, palette: &Palette, cell: &cell::Position, size: row::Size, options: Options) -> bool {
 		// Bail out if the cell is up to date.
-		if !self.cache.update(cell, options) && !options.damage() {
+		if !self.cache.update(cell, size, options) && !options.damage() {
 			return false;
 		}
 
 		let (c, o, f) = (state.config(), &mut self.context, state.font());
+		let (sx, sy, shift) = scale(size);
+
+		// Cells past half the columns of a double width line are not shown, only
+		// the leftover column of an odd width has to be cleared.
+		if sx != 1 && cell.x() >= state.columns() / 2 {
+			if cell.x() == state.columns() - 1 && state.columns() % 2 == 1 {
+				let h = f.height() + c.style().spacing();
+
+				o.rectangle((state.margin().horizontal + cell.x() * f.width()) as f64,
+					(state.margin().vertical + cell.y() * h) as f64, f.width() as f64, h as f64);
+				o.rgba(palette.background());
+				o.fill();
+			}
+
+			return true;
+		}
 
 		let mut fg = cell.style().foreground().unwrap_or_else(||
-			c.style().color().foreground());
+			palette.foreground());
 
 		let mut bg = cell.style().background().unwrap_or_else(||
-			c.style().color().background());
+			palette.background());
 
 		if options.reverse() {
 			mem::swap(&mut fg, &mut bg);
@@ -243,9 +308,9 @@ impl Renderer {
 			mem::swap(&mut fg, &mut bg);
 		}
 
-		let w = f.width() * cell.width();
+		let w = f.width() * cell.width() * sx;
 		let h = f.height() + c.style().spacing();
-		let x = state.margin().horizontal + (cell.x() * f.width());
+		let x = state.margin().horizontal + (cell.x() * f.width() * sx);
 		let y = state.margin().vertical + (cell.y() * h);
 
 		o.save();
@@ -256,6 +321,14 @@ impl Renderer {
 			o.rgba(bg);
 			o.paint();
 
+			// Draw the images below the text.
+			images(o, state, &self.images, (x, y, w, h), true);
+
+			// Draw the rest relative to the cell, scaled to the line size.
+			o.translate(x as f64, y as f64 - (shift * h) as f64);
+			o.scale(sx as f64, sy as f64);
+			let (x, y, w) = (0, 0, w / sx);
+
 			// Draw the glyph.
 			if !cell.style().attributes().contains(style::BLINK) || !options.blinking() {
 				if cell.is_occupied() {
@@ -269,13 +342,66 @@ impl Renderer {
 					o.image(cell.image(), x as f64, y as f64);
 				}
 
-				// Draw underline.
-				if cell.style().attributes().contains(style::UNDERLINE) {
+				// Draw underline, hovered links are always underlined.
+				let attributes = cell.style().attributes();
+				if attributes.intersects(style::UNDERLINES) ||
+				   (cell.link().is_some() && Link::same(cell.link(), self.hover.as_ref()))
+				{
 					let (thickness, position) = f.underline();
+					let (x, y, w, t)          = (x as f64, (y + position) as f64, w as f64, thickness as f64);
 
-					o.rgba(c.style().color().underline().unwrap_or(fg));
-					o.rectangle(x as f64, (y + position) as f64, w as f64, thickness as f64);
-					o.line_width(1.0);
+					o.rgba(cell.style().underline().or(c.style().color().underline()).unwrap_or(fg));
+
+					if attributes.contains(style::UNDERLINE_DOUBLE) {
+						o.rectangle(x, y, w, t);
+						o.rectangle(x, y + t * 2.0, w, t);
+						o.fill();
+					}
+					else if attributes.contains(style::UNDERLINE_CURLY) {
+						// One wave per cell, so it stays continuous between cells.
+						let steps = 8 * cell.width();
+						let width = f.width() as f64;
+
+						o.move_to(x, y);
+						for i in 1 ... steps {
+							let offset = w * i as f64 / steps as f64;
+							o.line_to(x + offset, y + t * (offset / width * 2.0 * f64::consts::PI).sin());
+						}
+
+						o.line_width(t);
+						o.stroke();
+					}
+					else if attributes.contains(style::UNDERLINE_DOTTED) {
+						let mut offset = 0.0;
+						while offset < w {
+							o.rectangle(x + offset, y, t, t);
+							offset += t * 2.0;
+						}
+
+						o.fill();
+					}
+					else if attributes.contains(style::UNDERLINE_DASHED) {
+						let width = f.width() as f64;
+
+						for i in 0 .. cell.width() {
+							o.rectangle(x + i as f64 * width + width / 6.0, y, width * 2.0 / 3.0, t);
+						}
+
+						o.fill();
+					}
+					else {
+						o.rectangle(x, y, w, t);
+						o.line_width(1.0);
+						o.fill();
+					}
+				}
+
+				// Draw overline.
+				if attributes.contains(style::OVERLINE) {
+					let (thickness, _) = f.underline();
+
+					o.rgba(fg);
+					o.rectangle(x as f64, y as f64, w as f64, thickness as f64);
 					o.fill();
 				}
 
@@ -292,6 +418,56 @@ impl Renderer {
 		}
 		o.restore();
 
+		// Draw the images above the text.
+		images(o, state, &self.images, (x, y, w, h), false);
+
 		true
 	}
 }
+
+/// Draw the parts of the images within the given cell area, either the ones
+/// below or above the text.
+fn images(o: &mut cairo::Context, state: &State, images: &[(i32, Rc<Placement>)], (x, y, w, h): (u32, u32, u32, u32), below: bool) {
+	let f    = state.font();
+	let line = f.height() + state.config().style().spacing();
+
+	for &(row, ref placement) in images {
+		if (placement.z() < 0) != below {
+			continue;
+		}
+
+		let (width, height) = placement.size();
+		let left = state.margin().horizontal as i32 + (placement.x() * f.width() + placement.offset().0) as i32;
+		let top  = state.margin().vertical as i32 + row * line as i32 + placement.offset().1 as i32;
+
+		// Skip images not covering the cell.
+		if left + width as i32 <= x as i32 || left >= (x + w) as i32 ||
+		   top + height as i32 <= y as i32 || top >= (y + h) as i32
+		{
+			continue;
+		}
+
+		let (sx, sy, sw, sh) = placement.source();
+
+		o.save();
+		o.rectangle(x as f64, y as f64, w as f64, h as f64);
+		o.clip();
+		o.rectangle(left as f64, top as f64, width as f64, height as f64);
+		o.clip();
+		o.translate(left as f64, top as f64);
+		o.scale(width as f64 / sw as f64, height as f64 / sh as f64);
+		o.image(placement.buffer(), -(sx as f64), -(sy as f64));
+		o.restore();
+	}
+}
+
+/// Get the horizontal and vertical scale for the line size, and how many
+/// cell heights the line is shifted up by.
+fn scale(size: row::Size) -> (u32, u32, u32) {
+	match size {
+		row::Size::Single => (1, 1, 0),
+		row::Size::Double => (2, 1, 0),
+		row::Size::Top    => (2, 2, 0),
+		row::Size::Bottom => (2, 2, 1),
+	}
+}
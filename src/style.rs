@@ -22,10 +22,11 @@ pub struct Style {
 	pub foreground: Option<Rgba<f64>>,
 	pub background: Option<Rgba<f64>>,
 	pub attributes: Attributes,
+	pub underline:  Option<Rgba<f64>>,
 }
 
 bitflags! {
-	pub flags Attributes: u8 {
+	pub flags Attributes: u16 {
 		const NONE      = 0,
 		const BOLD      = 1 << 0,
 		const FAINT     = 1 << 1,
@@ -35,6 +36,16 @@ bitflags! {
 		const REVERSE   = 1 << 5,
 		const INVISIBLE = 1 << 6,
 		const STRUCK    = 1 << 7,
+		const PROTECTED = 1 << 8,
+		const OVERLINE  = 1 << 9,
+
+		const UNDERLINE_DOUBLE = 1 << 10,
+		const UNDERLINE_CURLY  = 1 << 11,
+		const UNDERLINE_DOTTED = 1 << 12,
+		const UNDERLINE_DASHED = 1 << 13,
+
+		const UNDERLINES = UNDERLINE.bits | UNDERLINE_DOUBLE.bits | UNDERLINE_CURLY.bits |
+			UNDERLINE_DOTTED.bits | UNDERLINE_DASHED.bits,
 	}
 }
 
@@ -44,6 +55,7 @@ impl Default for Style {
 			foreground: None,
 			background: None,
 			attributes: Attributes::empty(),
+			underline:  None,
 		}
 	}
 }
@@ -60,4 +72,8 @@ impl Style {
 	pub fn attributes(&self) -> Attributes {
 		self.attributes
 	}
+
+	pub fn underline(&self) -> Option<&Rgba<f64>> {
+		self.underline.as_ref()
+	}
 }
@@ -16,13 +16,14 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
+use std::ffi::CString;
 
 use libc::c_int;
 use ffi::cairo::*;
 use ffi::pango::*;
 use sys::pango;
 use picto::color::{Rgb, Rgba};
-use super::{Surface, Image};
+use super::{Surface, Image, Operator};
 
 pub struct Context(pub *mut cairo_t);
 
@@ -57,6 +58,18 @@ impl Context {
 		}
 	}
 
+	pub fn translate(&mut self, x: f64, y: f64) {
+		unsafe {
+			cairo_translate(self.0, x, y);
+		}
+	}
+
+	pub fn scale(&mut self, x: f64, y: f64) {
+		unsafe {
+			cairo_scale(self.0, x, y);
+		}
+	}
+
 	pub fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64) {
 		unsafe {
 			cairo_rectangle(self.0, x, y, width, height);
@@ -81,6 +94,24 @@ impl Context {
 		}
 	}
 
+	pub fn operator(&mut self, op: Operator) {
+		unsafe {
+			cairo_set_operator(self.0, op);
+		}
+	}
+
+	pub fn new_path(&mut self) {
+		unsafe {
+			cairo_new_path(self.0);
+		}
+	}
+
+	pub fn close_path(&mut self) {
+		unsafe {
+			cairo_close_path(self.0);
+		}
+	}
+
 	pub fn move_to(&mut self, x: f64, y: f64) {
 		unsafe {
 			cairo_move_to(self.0, x, y);
@@ -93,6 +124,23 @@ impl Context {
 		}
 	}
 
+	pub fn curve_to(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), (x3, y3): (f64, f64)) {
+		unsafe {
+			cairo_curve_to(self.0, x1, y1, x2, y2, x3, y3);
+		}
+	}
+
+	pub fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+		unsafe {
+			if start <= end {
+				cairo_arc(self.0, x, y, radius, start, end);
+			}
+			else {
+				cairo_arc_negative(self.0, x, y, radius, start, end);
+			}
+		}
+	}
+
 	pub fn line_width(&mut self, w: f64) {
 		unsafe {
 			cairo_set_line_width(self.0, w);
@@ -117,6 +165,25 @@ impl Context {
 		}
 	}
 
+	pub fn font_size(&mut self, size: f64) {
+		unsafe {
+			cairo_set_font_size(self.0, size);
+		}
+	}
+
+	/// Draw text with the toy font API, returning how far it advanced.
+	pub fn text<T: AsRef<str>>(&mut self, text: T) -> (f64, f64) {
+		let text = CString::new(text.as_ref().replace('\0', "")).unwrap();
+
+		unsafe {
+			let mut extents = mem::uninitialized();
+			cairo_text_extents(self.0, text.as_ptr(), &mut extents);
+			cairo_show_text(self.0, text.as_ptr());
+
+			(extents.x_advance, extents.y_advance)
+		}
+	}
+
 	pub fn glyph<T: AsRef<str>>(&mut self, text: T, glyph: &pango::GlyphItem) {
 		let text = text.as_ref();
 
@@ -16,10 +16,13 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ptr;
+use std::slice;
 use std::cell::Cell;
+use std::os::raw::c_void;
 
-use libc::{c_uchar, c_int};
+use libc::{c_uchar, c_int, c_uint};
 use ffi::cairo::*;
+use super::Surface;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Image {
@@ -48,6 +51,64 @@ impl Image {
 		}
 	}
 
+	/// Decode a PNG image, `None` if the data is invalid.
+	pub fn png(data: &[u8]) -> Option<Self> {
+		extern "C" fn read(closure: *mut c_void, data: *mut c_uchar, length: c_uint) -> cairo_status_t {
+			unsafe {
+				let input  = &mut *(closure as *mut &[u8]);
+				let length = length as usize;
+
+				if input.len() < length {
+					return CAIRO_STATUS_READ_ERROR;
+				}
+
+				ptr::copy_nonoverlapping(input.as_ptr(), data, length);
+				*input = &input[length ..];
+
+				CAIRO_STATUS_SUCCESS
+			}
+		}
+
+		unsafe {
+			let mut input   = data;
+			let     surface = cairo_image_surface_create_from_png_stream(read, &mut input as *mut &[u8] as *mut c_void);
+			let     format  = cairo_image_surface_get_format(surface);
+
+			if cairo_surface_status(surface) != CAIRO_STATUS_SUCCESS ||
+			   (format != cairo_format_t::Argb32 && format != cairo_format_t::Rgb24)
+			{
+				cairo_surface_destroy(surface);
+				return None;
+			}
+
+			cairo_surface_flush(surface);
+
+			let width     = cairo_image_surface_get_width(surface) as u32;
+			let height    = cairo_image_surface_get_height(surface) as u32;
+			let stride    = cairo_image_surface_get_stride(surface) as usize;
+			let source    = slice::from_raw_parts(cairo_image_surface_get_data(surface), stride * height as usize);
+			let mut image = Image::new(width, height);
+
+			for y in 0 .. height as usize {
+				let from = y * stride;
+				let to   = y * image.stride as usize;
+
+				image.buffer[to .. to + width as usize * 4]
+					.copy_from_slice(&source[from .. from + width as usize * 4]);
+
+				// The unused byte is undefined without an alpha channel.
+				if format == cairo_format_t::Rgb24 {
+					for px in image.buffer[to .. to + width as usize * 4].chunks_mut(4) {
+						px[3] = 255;
+					}
+				}
+			}
+
+			cairo_surface_destroy(surface);
+			Some(image)
+		}
+	}
+
 	pub fn width(&self) -> u32 {
 		self.width
 	}
@@ -69,6 +130,45 @@ impl Image {
 		self.buffer[offset + 3] = a;
 	}
 
+	/// Check if every pixel is fully transparent.
+	pub fn is_empty(&self) -> bool {
+		self.buffer.chunks(4).all(|px| px[3] == 0)
+	}
+
+	/// Copy the given area into a new image.
+	pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Image {
+		let mut image = Image::new(width, height);
+
+		for row in 0 .. height {
+			let from = ((x * 4) + ((y + row) * self.stride)) as usize;
+			let to   = (row * image.stride) as usize;
+
+			image.buffer[to .. to + (width * 4) as usize]
+				.copy_from_slice(&self.buffer[from .. from + (width * 4) as usize]);
+		}
+
+		image
+	}
+
+	/// Copy the given image at the given position.
+	pub fn paste(&mut self, image: &Image, x: u32, y: u32) {
+		for row in 0 .. image.height {
+			let from = (row * image.stride) as usize;
+			let to   = ((x * 4) + ((y + row) * self.stride)) as usize;
+
+			self.buffer[to .. to + (image.width * 4) as usize]
+				.copy_from_slice(&image.buffer[from .. from + (image.width * 4) as usize]);
+		}
+	}
+
+	/// Create a surface to draw on the image.
+	pub fn surface(&mut self) -> Surface {
+		unsafe {
+			Surface(cairo_image_surface_create_for_data(self.buffer.as_ptr(), cairo_format_t::Argb32,
+				self.width as c_int, self.height as c_int, self.stride as c_int))
+		}
+	}
+
 	pub fn pattern(&self) -> *mut cairo_pattern_t {
 		unsafe {
 			if let Some(ptr) = self.pattern.get().as_mut() {
@@ -23,3 +23,5 @@ pub use self::context::Context;
 
 mod image;
 pub use self::image::Image;
+
+pub use ffi::cairo::cairo_operator_t as Operator;
@@ -22,6 +22,7 @@ use unicode_width::UnicodeWidthStr;
 
 use style::Style;
 use sys::cairo;
+use terminal::Link;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Cell {
@@ -37,6 +38,8 @@ pub enum Cell {
 	Occupied {
 		style: Rc<Style>,
 		value: String,
+		width: u8,
+		link:  Option<Rc<Link>>,
 	},
 
 	Reference(u8),
@@ -69,8 +72,10 @@ impl Cell {
 	/// Create an occupied cell.
 	pub fn occupied(value: String, style: Rc<Style>) -> Self {
 		Cell::Occupied {
+			width: value.width() as u8,
 			value: value,
 			style: style,
+			link:  None,
 		}
 	}
 
@@ -138,8 +143,8 @@ impl Cell {
 			Cell::Image { .. } =>
 				false,
 
-			Cell::Occupied { ref value, .. } =>
-				value.width() > 1,
+			Cell::Occupied { width, .. } =>
+				width > 1,
 
 			Cell::Reference(..) =>
 				unreachable!()
@@ -155,12 +160,23 @@ impl Cell {
 
 	/// Make the cell occupied.
 	pub fn make_occupied<T: Into<String>>(&mut self, value: T, style: Rc<Style>) {
+		let value = value.into();
+
 		mem::replace(self, Cell::Occupied {
-			value: value.into(),
+			width: value.width() as u8,
+			value: value,
 			style: style,
+			link:  None,
 		});
 	}
 
+	/// Append to the value in place, the width is left untouched.
+	pub fn append<T: AsRef<str>>(&mut self, other: T) {
+		if let Cell::Occupied { ref mut value, .. } = *self {
+			value.push_str(other.as_ref());
+		}
+	}
+
 	/// Make the cell into a reference.
 	pub fn make_reference(&mut self, offset: u8) {
 		mem::replace(self, Cell::Reference(offset));
@@ -187,6 +203,23 @@ impl Cell {
 		}
 	}
 
+	/// Change the link in place.
+	pub fn set_link(&mut self, value: Option<Rc<Link>>) {
+		if let Cell::Occupied { ref mut link, .. } = *self {
+			*link = value;
+		}
+	}
+
+	/// Get the link if any.
+	pub fn link(&self) -> Option<&Rc<Link>> {
+		if let Cell::Occupied { ref link, .. } = *self {
+			link.as_ref()
+		}
+		else {
+			None
+		}
+	}
+
 	/// Get the cell style.
 	pub fn style(&self) -> &Rc<Style> {
 		match *self {
@@ -222,8 +255,8 @@ impl Cell {
 			Cell::Image { .. } =>
 				1,
 
-			Cell::Occupied { ref value, .. } =>
-				value.width() as u32,
+			Cell::Occupied { width, .. } =>
+				width as u32,
 
 			Cell::Reference(..) =>
 				unreachable!(),
@@ -0,0 +1,302 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use control::DEC;
+use control::DEC::charset::{DEC as Special, NRCS};
+
+/// Convert the character from the given charset.
+pub fn convert<'a>(charset: DEC::Charset, value: &'a str) -> &'a str {
+	match charset {
+		DEC::Charset::DEC(Special::Graphic) =>
+			graphic(value),
+
+		DEC::Charset::DEC(Special::Technical) =>
+			technical(value),
+
+		DEC::Charset::DEC(Special::Supplemental) =>
+			supplemental(value),
+
+		DEC::Charset::NRCS(nrcs) =>
+			national(nrcs, value),
+
+		_ =>
+			value
+	}
+}
+
+/// The DEC Special Graphics charset.
+fn graphic(value: &str) -> &str {
+	match value {
+		"A" => "↑",
+		"B" => "↓",
+		"C" => "→",
+		"D" => "←",
+		"E" => "█",
+		"F" => "▚",
+		"G" => "☃",
+		"_" => " ",
+		"`" => "◆",
+		"a" => "▒",
+		"b" => "␉",
+		"c" => "␌",
+		"d" => "␍",
+		"e" => "␊",
+		"f" => "°",
+		"g" => "±",
+		"h" => "␤",
+		"i" => "␋",
+		"j" => "┘",
+		"k" => "┐",
+		"l" => "┌",
+		"m" => "└",
+		"n" => "┼",
+		"o" => "⎺",
+		"p" => "⎻",
+		"q" => "─",
+		"r" => "⎼",
+		"s" => "⎽",
+		"t" => "├",
+		"u" => "┤",
+		"v" => "┴",
+		"w" => "┬",
+		"x" => "│",
+		"y" => "≤",
+		"z" => "≥",
+		"{" => "π",
+		"|" => "≠",
+		"}" => "£",
+		"~" => "·",
+		_   => value,
+	}
+}
+
+/// The DEC Technical charset, characters without a Unicode counterpart are
+/// left as they are.
+fn technical(value: &str) -> &str {
+	match value {
+		"!"  => "⎷",
+		"\"" => "┌",
+		"#"  => "─",
+		"$"  => "⌠",
+		"%"  => "⌡",
+		"&"  => "│",
+		"'"  => "⎡",
+		"("  => "⎣",
+		")"  => "⎤",
+		"*"  => "⎦",
+		"+"  => "⎛",
+		","  => "⎝",
+		"-"  => "⎞",
+		"."  => "⎠",
+		"/"  => "⎨",
+		"0"  => "⎬",
+		"<"  => "≤",
+		"="  => "≠",
+		">"  => "≥",
+		"?"  => "∫",
+		"@"  => "∴",
+		"A"  => "∝",
+		"B"  => "∞",
+		"C"  => "÷",
+		"D"  => "Δ",
+		"E"  => "∇",
+		"F"  => "Φ",
+		"G"  => "Γ",
+		"H"  => "∼",
+		"I"  => "≃",
+		"J"  => "Θ",
+		"K"  => "×",
+		"L"  => "Λ",
+		"M"  => "⇔",
+		"N"  => "⇒",
+		"O"  => "≡",
+		"P"  => "Π",
+		"Q"  => "Ψ",
+		"S"  => "Σ",
+		"V"  => "√",
+		"W"  => "Ω",
+		"X"  => "Ξ",
+		"Y"  => "Υ",
+		"Z"  => "⊂",
+		"["  => "⊃",
+		"\\" => "∩",
+		"]"  => "∪",
+		"^"  => "∧",
+		"_"  => "∨",
+		"`"  => "¬",
+		"a"  => "α",
+		"b"  => "β",
+		"c"  => "χ",
+		"d"  => "δ",
+		"e"  => "ε",
+		"f"  => "φ",
+		"g"  => "γ",
+		"h"  => "η",
+		"i"  => "ι",
+		"j"  => "θ",
+		"k"  => "κ",
+		"l"  => "λ",
+		"n"  => "ν",
+		"o"  => "∂",
+		"p"  => "π",
+		"q"  => "ψ",
+		"r"  => "ρ",
+		"s"  => "σ",
+		"t"  => "τ",
+		"v"  => "ƒ",
+		"w"  => "ω",
+		"x"  => "ξ",
+		"y"  => "υ",
+		"z"  => "ζ",
+		"{"  => "←",
+		"|"  => "↑",
+		"}"  => "→",
+		"~"  => "↓",
+		_    => value,
+	}
+}
+
+/// The DEC Supplemental charset, which is mostly the upper half of Latin-1.
+fn supplemental(value: &str) -> &str {
+	const LATIN1: [&'static str; 94] = [
+		"¡", "¢", "£", "¤", "¥", "¦", "§", "¤", "©", "ª", "«", "¬", "\u{AD}", "®", "¯",
+		"°", "±", "²", "³", "´", "µ", "¶", "·", "¸", "¹", "º", "»", "¼", "½", "¾", "¿",
+		"À", "Á", "Â", "Ã", "Ä", "Å", "Æ", "Ç", "È", "É", "Ê", "Ë", "Ì", "Í", "Î", "Ï",
+		"Ð", "Ñ", "Ò", "Ó", "Ô", "Õ", "Ö", "Œ", "Ø", "Ù", "Ú", "Û", "Ü", "Ÿ", "Þ", "ß",
+		"à", "á", "â", "ã", "ä", "å", "æ", "ç", "è", "é", "ê", "ë", "ì", "í", "î", "ï",
+		"ð", "ñ", "ò", "ó", "ô", "õ", "ö", "œ", "ø", "ù", "ú", "û", "ü", "ÿ", "þ",
+	];
+
+	if value.len() == 1 && value.as_bytes()[0] > 0x20 && value.as_bytes()[0] < 0x7F {
+		LATIN1[(value.as_bytes()[0] - 0x21) as usize]
+	}
+	else {
+		value
+	}
+}
+
+/// The national replacement charsets.
+fn national(nrcs: NRCS, value: &str) -> &str {
+	match (nrcs, value) {
+		(NRCS::UK, "#") => "£",
+
+		(NRCS::German, "@")  => "§",
+		(NRCS::German, "[")  => "Ä",
+		(NRCS::German, "\\") => "Ö",
+		(NRCS::German, "]")  => "Ü",
+		(NRCS::German, "{")  => "ä",
+		(NRCS::German, "|")  => "ö",
+		(NRCS::German, "}")  => "ü",
+		(NRCS::German, "~")  => "ß",
+
+		(NRCS::French, "#")  => "£",
+		(NRCS::French, "@")  => "à",
+		(NRCS::French, "[")  => "°",
+		(NRCS::French, "\\") => "ç",
+		(NRCS::French, "]")  => "§",
+		(NRCS::French, "{")  => "é",
+		(NRCS::French, "|")  => "ù",
+		(NRCS::French, "}")  => "è",
+		(NRCS::French, "~")  => "¨",
+
+		(NRCS::FrenchCanadian, "@")  => "à",
+		(NRCS::FrenchCanadian, "[")  => "â",
+		(NRCS::FrenchCanadian, "\\") => "ç",
+		(NRCS::FrenchCanadian, "]")  => "ê",
+		(NRCS::FrenchCanadian, "^")  => "î",
+		(NRCS::FrenchCanadian, "`")  => "ô",
+		(NRCS::FrenchCanadian, "{")  => "é",
+		(NRCS::FrenchCanadian, "|")  => "ù",
+		(NRCS::FrenchCanadian, "}")  => "è",
+		(NRCS::FrenchCanadian, "~")  => "û",
+
+		(NRCS::Norwegian, "@")  => "Ä",
+		(NRCS::Norwegian, "[")  => "Æ",
+		(NRCS::Norwegian, "\\") => "Ø",
+		(NRCS::Norwegian, "]")  => "Å",
+		(NRCS::Norwegian, "^")  => "Ü",
+		(NRCS::Norwegian, "`")  => "ä",
+		(NRCS::Norwegian, "{")  => "æ",
+		(NRCS::Norwegian, "|")  => "ø",
+		(NRCS::Norwegian, "}")  => "å",
+		(NRCS::Norwegian, "~")  => "ü",
+
+		(NRCS::Finnish, "[")  => "Ä",
+		(NRCS::Finnish, "\\") => "Ö",
+		(NRCS::Finnish, "]")  => "Å",
+		(NRCS::Finnish, "^")  => "Ü",
+		(NRCS::Finnish, "`")  => "é",
+		(NRCS::Finnish, "{")  => "ä",
+		(NRCS::Finnish, "|")  => "ö",
+		(NRCS::Finnish, "}")  => "å",
+		(NRCS::Finnish, "~")  => "ü",
+
+		(NRCS::Swedish, "@")  => "É",
+		(NRCS::Swedish, "[")  => "Ä",
+		(NRCS::Swedish, "\\") => "Ö",
+		(NRCS::Swedish, "]")  => "Å",
+		(NRCS::Swedish, "^")  => "Ü",
+		(NRCS::Swedish, "`")  => "é",
+		(NRCS::Swedish, "{")  => "ä",
+		(NRCS::Swedish, "|")  => "ö",
+		(NRCS::Swedish, "}")  => "å",
+		(NRCS::Swedish, "~")  => "ü",
+
+		(NRCS::Italian, "#")  => "£",
+		(NRCS::Italian, "@")  => "§",
+		(NRCS::Italian, "[")  => "°",
+		(NRCS::Italian, "\\") => "ç",
+		(NRCS::Italian, "]")  => "é",
+		(NRCS::Italian, "`")  => "ù",
+		(NRCS::Italian, "{")  => "à",
+		(NRCS::Italian, "|")  => "ò",
+		(NRCS::Italian, "}")  => "è",
+		(NRCS::Italian, "~")  => "ì",
+
+		(NRCS::Swiss, "#")  => "ù",
+		(NRCS::Swiss, "@")  => "à",
+		(NRCS::Swiss, "[")  => "é",
+		(NRCS::Swiss, "\\") => "ç",
+		(NRCS::Swiss, "]")  => "ê",
+		(NRCS::Swiss, "^")  => "î",
+		(NRCS::Swiss, "_")  => "è",
+		(NRCS::Swiss, "`")  => "ô",
+		(NRCS::Swiss, "{")  => "ä",
+		(NRCS::Swiss, "|")  => "ö",
+		(NRCS::Swiss, "}")  => "ü",
+		(NRCS::Swiss, "~")  => "û",
+
+		(NRCS::Spanish, "#")  => "£",
+		(NRCS::Spanish, "@")  => "§",
+		(NRCS::Spanish, "[")  => "¡",
+		(NRCS::Spanish, "\\") => "Ñ",
+		(NRCS::Spanish, "]")  => "¿",
+		(NRCS::Spanish, "{")  => "°",
+		(NRCS::Spanish, "|")  => "ñ",
+		(NRCS::Spanish, "}")  => "ç",
+
+		(NRCS::Portuguese, "[")  => "Ã",
+		(NRCS::Portuguese, "\\") => "Ç",
+		(NRCS::Portuguese, "]")  => "Õ",
+		(NRCS::Portuguese, "{")  => "ã",
+		(NRCS::Portuguese, "|")  => "ç",
+		(NRCS::Portuguese, "}")  => "õ",
+
+		_ =>
+			value
+	}
+}
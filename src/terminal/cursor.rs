@@ -38,11 +38,14 @@ pub struct Cursor {
 
 	pub state:  State,
 	pub scroll: (u32, u32),
+	pub margin: (u32, u32),
 	pub style:  Rc<Style>,
 	pub bright: Option<u8>,
 
 	pub charsets: [DEC::Charset; 4],
 	pub charset:  u8,
+	pub right:    Option<u8>,
+	pub shift:    Option<u8>,
 
 	pub foreground: Rgba<f64>,
 	pub background: Rgba<f64>,
@@ -94,11 +97,14 @@ impl Cursor {
 
 			state:  state,
 			scroll: (0, height - 1),
+			margin: (0, width - 1),
 			style:  Default::default(),
 			bright: None,
 
 			charsets: [DEC::charset::ISO::Latin2.into(); 4],
 			charset:  0,
+			right:    None,
+			shift:    None,
 
 			foreground: *config.style().cursor().foreground(),
 			background: *config.style().cursor().background(),
@@ -112,6 +118,10 @@ impl Cursor {
 			self.scroll = (0, height - 1);
 		}
 
+		if self.margin == (0, self.width - 1) || self.margin.1 >= width {
+			self.margin = (0, width - 1);
+		}
+
 		if self.x >= width {
 			self.x = width - 1;
 		}
@@ -179,6 +189,11 @@ impl Cursor {
 		self.scroll
 	}
 
+	/// Get the left and right margins.
+	pub fn margin(&self) -> (u32, u32) {
+		self.margin
+	}
+
 	/// Update the current style if needed.
 	pub fn update(&mut self, style: Style) {
 		if &*self.style != &style {
@@ -196,9 +211,18 @@ impl Cursor {
 
 		match value {
 			Position(x, y) => {
-				if let Some(x) = x {
-					if x >= self.width {
-						self.x = self.width - 1;
+				if let Some(mut x) = x {
+					// In origin mode the cursor stays within the margins.
+					let right = if self.state.contains(ORIGIN) {
+						x = x.saturating_add(self.margin.0);
+						self.margin.1
+					}
+					else {
+						self.width - 1
+					};
+
+					if x > right {
+						self.x = right;
 					}
 					else {
 						self.x = x;
@@ -243,12 +267,15 @@ impl Cursor {
 				}
 			}
 
+			// Movement stops at the margins, unless the cursor is already outside
+			// them.
 			Left(n) => {
-				let new = (self.x as i32).saturating_sub(i32::try_from(n).unwrap_or(i32::MAX));
+				let new  = (self.x as i32).saturating_sub(i32::try_from(n).unwrap_or(i32::MAX));
+				let edge = if self.x >= self.margin.0 { self.margin.0 } else { 0 };
 
-				if new < 0 {
-					self.x = 0;
-					overflow = Some(new.abs());
+				if new < edge as i32 {
+					self.x = edge;
+					overflow = Some(edge as i32 - new);
 				}
 				else {
 					self.x = new as u32;
@@ -256,11 +283,12 @@ impl Cursor {
 			}
 
 			Right(n) => {
-				let new = (self.x as i32).saturating_add(i32::try_from(n).unwrap_or(i32::MAX));
+				let new  = (self.x as i32).saturating_add(i32::try_from(n).unwrap_or(i32::MAX));
+				let edge = if self.x <= self.margin.1 { self.margin.1 } else { self.width - 1 };
 
-				if new >= self.width as i32 {
-					self.x = self.width - 1;
-					overflow = Some(new - (self.width as i32 - 1));
+				if new > edge as i32 {
+					self.x = edge;
+					overflow = Some(new - edge as i32);
 				}
 				else {
 					self.x = new as u32;
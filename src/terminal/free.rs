@@ -19,6 +19,7 @@ use std::rc::Rc;
 use std::collections::{VecDeque, LinkedList};
 
 use terminal::{Cell, Row};
+use terminal::row;
 use style::Style;
 
 /// Wrapper for `Row` reuse.
@@ -52,6 +53,10 @@ impl Free {
 		match self.inner.pop_front() {
 			Some(mut row) => {
 				row.wrapped = false;
+				row.mark    = row::Mark::empty();
+				row.status  = None;
+				row.size    = row::Size::Single;
+				row.images.clear();
 				row.resize(cols, Cell::empty(self.empty.clone()));
 
 				for cell in row.iter_mut().filter(|c| !c.is_default()) {
@@ -65,6 +70,10 @@ impl Free {
 				Row {
 					inner:   vec_deque![Cell::empty(self.empty.clone()); cols],
 					wrapped: false,
+					mark:    row::Mark::empty(),
+					status:  None,
+					size:    row::Size::Single,
+					images:  Vec::new(),
 				}
 			}
 		}
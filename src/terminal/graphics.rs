@@ -0,0 +1,572 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::rc::{Rc, Weak};
+use std::cmp;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
+
+use base64;
+use sys::cairo;
+
+/// The maximum width and height of a transmitted image.
+pub const LIMIT: u32 = 10_000;
+
+/// The maximum number of bytes kept for stored images, also the most read
+/// from a file.
+const QUOTA: usize = 320 * 1024 * 1024;
+
+/// Errors reported back to the application.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Error {
+	NotFound,
+	Invalid,
+	BadFile,
+	NoData,
+	TooBig,
+	Unsupported,
+}
+
+impl Error {
+	/// Get the error code and message of the response.
+	pub fn code(&self) -> &'static str {
+		match *self {
+			Error::NotFound    => "ENOENT:image not found",
+			Error::Invalid     => "EINVAL:invalid command or image data",
+			Error::BadFile     => "EBADF:could not read file",
+			Error::NoData      => "ENODATA:insufficient image data",
+			Error::TooBig      => "EFBIG:image too big",
+			Error::Unsupported => "ENOTSUP:unsupported transmission",
+		}
+	}
+}
+
+/// A graphics command, the control data followed by the payload.
+#[derive(Clone, Debug)]
+pub struct Command {
+	keys:    HashMap<u8, String, BuildHasherDefault<FnvHasher>>,
+	payload: String,
+}
+
+impl Command {
+	/// Parse the body of the command, malformed keys are ignored.
+	pub fn parse(string: &str) -> Self {
+		let (control, payload) = match string.find(';') {
+			Some(index) => (&string[.. index], &string[index + 1 ..]),
+			None        => (string, ""),
+		};
+
+		Command {
+			keys: control.split(',').filter_map(|pair| {
+				let mut parts = pair.splitn(2, '=');
+
+				match (parts.next(), parts.next()) {
+					(Some(key), Some(value)) if key.len() == 1 =>
+						Some((key.as_bytes()[0], value.to_owned())),
+
+					_ =>
+						None
+				}
+			}).collect(),
+
+			payload: payload.to_owned(),
+		}
+	}
+
+	/// Get the raw value of a key.
+	pub fn get(&self, key: u8) -> Option<&str> {
+		self.keys.get(&key).map(AsRef::as_ref)
+	}
+
+	/// Get an unsigned value, or the default if missing or invalid.
+	pub fn number(&self, key: u8, default: u32) -> u32 {
+		self.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+	}
+
+	/// Get a signed value, or the default if missing or invalid.
+	pub fn integer(&self, key: u8, default: i32) -> i32 {
+		self.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+	}
+
+	/// Get a single character value, or the default if missing.
+	pub fn character(&self, key: u8, default: u8) -> u8 {
+		self.get(key).and_then(|v| v.bytes().next()).unwrap_or(default)
+	}
+
+	/// Get the action, transmission by default.
+	pub fn action(&self) -> u8 {
+		self.character(b'a', b't')
+	}
+
+	/// Get the base64 encoded payload.
+	pub fn payload(&self) -> &str {
+		&self.payload
+	}
+}
+
+/// An image placed on the grid, anchored to the row holding it.
+#[derive(PartialEq, Debug)]
+pub struct Placement {
+	image:  u32,
+	id:     u32,
+	buffer: Rc<cairo::Image>,
+
+	x:      u32,
+	source: (u32, u32, u32, u32),
+	offset: (u32, u32),
+	size:   (u32, u32),
+	cells:  (u32, u32),
+	z:      i32,
+}
+
+impl Placement {
+	/// Create a placement of the image at the given column, with the source
+	/// rectangle, offset, size and z-index from the command.
+	pub fn new(image: u32, buffer: Rc<cairo::Image>, x: u32, command: &Command, cell: (u32, u32)) -> Result<Self, Error> {
+		// The source rectangle, clamped to the image.
+		let left   = cmp::min(command.number(b'x', 0), buffer.width());
+		let top    = cmp::min(command.number(b'y', 0), buffer.height());
+		let width  = match command.number(b'w', 0) {
+			0 => buffer.width() - left,
+			n => cmp::min(n, buffer.width() - left),
+		};
+
+		let height = match command.number(b'h', 0) {
+			0 => buffer.height() - top,
+			n => cmp::min(n, buffer.height() - top),
+		};
+
+		if width == 0 || height == 0 {
+			return Err(Error::Invalid);
+		}
+
+		// The offset within the first cell.
+		let offset = (
+			cmp::min(command.number(b'X', 0), cell.0 - 1),
+			cmp::min(command.number(b'Y', 0), cell.1 - 1));
+
+		// The requested cells can't make the image bigger than a transmitted one.
+		let (columns, rows) = (
+			cmp::min(command.number(b'c', 0), cmp::max(1, LIMIT / cell.0)),
+			cmp::min(command.number(b'r', 0), cmp::max(1, LIMIT / cell.1)));
+
+		// The size is scaled to fill the requested cells, keeping the aspect ratio
+		// if only one side is given.
+		let size = match (columns, rows) {
+			(0, 0) =>
+				(width, height),
+
+			(columns, 0) => {
+				let w = columns * cell.0 - offset.0;
+				(w, cmp::max(1, (height as u64 * w as u64 / width as u64) as u32))
+			}
+
+			(0, rows) => {
+				let h = rows * cell.1 - offset.1;
+				(cmp::max(1, (width as u64 * h as u64 / height as u64) as u32), h)
+			}
+
+			(columns, rows) =>
+				(columns * cell.0 - offset.0, rows * cell.1 - offset.1),
+		};
+
+		let cells = (
+			if columns != 0 { columns } else { (offset.0 + size.0 + cell.0 - 1) / cell.0 },
+			if rows != 0 { rows } else { (offset.1 + size.1 + cell.1 - 1) / cell.1 });
+
+		Ok(Placement {
+			image:  image,
+			id:     command.number(b'p', 0),
+			buffer: buffer,
+
+			x:      x,
+			source: (left, top, width, height),
+			offset: offset,
+			size:   size,
+			cells:  cells,
+			z:      command.integer(b'z', 0),
+		})
+	}
+
+	/// Get the image ID.
+	pub fn image(&self) -> u32 {
+		self.image
+	}
+
+	/// Get the placement ID, 0 if none was given.
+	pub fn id(&self) -> u32 {
+		self.id
+	}
+
+	/// Get the image buffer.
+	pub fn buffer(&self) -> &cairo::Image {
+		&self.buffer
+	}
+
+	/// Get the column the image starts at.
+	pub fn x(&self) -> u32 {
+		self.x
+	}
+
+	/// Get the source rectangle within the image.
+	pub fn source(&self) -> (u32, u32, u32, u32) {
+		self.source
+	}
+
+	/// Get the pixel offset within the first cell.
+	pub fn offset(&self) -> (u32, u32) {
+		self.offset
+	}
+
+	/// Get the drawn size in pixels.
+	pub fn size(&self) -> (u32, u32) {
+		self.size
+	}
+
+	/// Get the number of columns covered.
+	pub fn columns(&self) -> u32 {
+		self.cells.0
+	}
+
+	/// Get the number of rows covered.
+	pub fn rows(&self) -> u32 {
+		self.cells.1
+	}
+
+	/// Get the z-index, negative values are drawn below the text.
+	pub fn z(&self) -> i32 {
+		self.z
+	}
+
+	/// Check if the previously drawn placements are the same as the current
+	/// ones, the weak references keep the allocations from being reused.
+	pub fn same(a: &[(i32, Weak<Placement>)], b: &[(i32, Rc<Placement>)]) -> bool {
+		a.len() == b.len() && a.iter().zip(b.iter()).all(|(&(ya, ref a), &(yb, ref b))|
+			ya == yb && a.upgrade().map(|a| &*a as *const Placement == &**b as *const Placement).unwrap_or(false))
+	}
+
+	/// Check if the placement anchored at row `y` covers the given cell.
+	pub fn covers(&self, y: i32, (column, row): (u32, u32)) -> bool {
+		column >= self.x && column < self.x + self.cells.0 &&
+		row as i32 >= y && (row as i32) < y + self.cells.1 as i32
+	}
+}
+
+/// Storage for transmitted images.
+#[derive(Default, Debug)]
+pub struct Graphics {
+	images:  HashMap<u32, Rc<cairo::Image>, BuildHasherDefault<FnvHasher>>,
+	numbers: HashMap<u32, u32, BuildHasherDefault<FnvHasher>>,
+	order:   Vec<u32>,
+	next:    u32,
+	pending: Option<Command>,
+}
+
+impl Graphics {
+	/// Feed the body of a command, returns it once all its chunks arrived.
+	pub fn feed(&mut self, string: &str) -> Option<Command> {
+		let chunk = Command::parse(string);
+		let more  = chunk.number(b'm', 0) == 1;
+
+		// Only the first chunk carries the control data.
+		// Payloads bigger than what could be stored are cut short, so they fail
+		// to decode.
+		let command = if let Some(mut pending) = self.pending.take() {
+			if pending.payload.len() < QUOTA / 3 * 4 {
+				pending.payload.push_str(&chunk.payload);
+			}
+
+			pending
+		}
+		else {
+			chunk
+		};
+
+		if more {
+			self.pending = Some(command);
+			None
+		}
+		else {
+			Some(command)
+		}
+	}
+
+	/// Decode and store the image, an ID is assigned if the command only has a
+	/// number or nothing at all.
+	pub fn load(&mut self, command: &Command) -> Result<(u32, Rc<cairo::Image>), Error> {
+		let image = Rc::new(try!(decode(command)));
+		let id    = match (command.number(b'i', 0), command.number(b'I', 0)) {
+			(0, 0) =>
+				self.id(),
+
+			(0, number) => {
+				let id = self.id();
+				self.numbers.insert(number, id);
+				id
+			}
+
+			(id, _) =>
+				id,
+		};
+
+		self.images.insert(id, image.clone());
+		self.order.retain(|&v| v != id);
+		self.order.push(id);
+		self.evict();
+
+		Ok((id, image))
+	}
+
+	/// Find the image the command refers to, by ID or number.
+	pub fn find(&self, command: &Command) -> Result<(u32, Rc<cairo::Image>), Error> {
+		let id = match (command.number(b'i', 0), command.number(b'I', 0)) {
+			(0, 0) =>
+				return Err(Error::Invalid),
+
+			(0, number) =>
+				try!(self.numbers.get(&number).cloned().ok_or(Error::NotFound)),
+
+			(id, _) =>
+				id,
+		};
+
+		self.images.get(&id).map(|image| (id, image.clone())).ok_or(Error::NotFound)
+	}
+
+	/// Get the ID of the newest image with the given number.
+	pub fn number(&self, number: u32) -> Option<u32> {
+		self.numbers.get(&number).cloned()
+	}
+
+	/// Drop the image data, unless it's still placed somewhere.
+	pub fn remove(&mut self, id: u32) {
+		if self.images.get(&id).map(|image| Rc::strong_count(image) > 1).unwrap_or(true) {
+			return;
+		}
+
+		self.images.remove(&id);
+		self.numbers.retain(|_, v| *v != id);
+		self.order.retain(|&v| v != id);
+	}
+
+	/// Drop the oldest images that aren't placed anywhere until the stored data
+	/// fits the quota.
+	fn evict(&mut self) {
+		let mut total = self.images.values().map(|image| size(image)).sum::<usize>();
+		let mut index = 0;
+
+		while total > QUOTA && index < self.order.len() {
+			let id = self.order[index];
+
+			if self.images.get(&id).map(|image| Rc::strong_count(image) > 1).unwrap_or(false) {
+				index += 1;
+				continue;
+			}
+
+			if let Some(image) = self.images.remove(&id) {
+				total -= size(&image);
+			}
+
+			self.numbers.retain(|_, v| *v != id);
+			self.order.remove(index);
+		}
+	}
+
+	/// Get an unused ID.
+	fn id(&mut self) -> u32 {
+		loop {
+			self.next = self.next.wrapping_add(1);
+
+			if self.next != 0 && !self.images.contains_key(&self.next) {
+				return self.next;
+			}
+		}
+	}
+}
+
+/// Decode the image transmitted by the command.
+pub fn decode(command: &Command) -> Result<cairo::Image, Error> {
+	// Compressed data is not supported.
+	if command.get(b'o').is_some() {
+		return Err(Error::Unsupported);
+	}
+
+	let payload = try!(base64::decode(command.payload()).map_err(|_| Error::Invalid));
+	let data    = match command.character(b't', b'd') {
+		b'd' =>
+			payload,
+
+		medium @ b'f' | medium @ b't' => {
+			let path = try!(String::from_utf8(payload).map_err(|_| Error::Invalid));
+			try!(read(&path, command.number(b'O', 0), command.number(b'S', 0), medium == b't'))
+		}
+
+		_ =>
+			return Err(Error::Unsupported)
+	};
+
+	match command.number(b'f', 32) {
+		100 => {
+			// The size is checked before decoding, so huge images are never allocated.
+			let (width, height) = try!(dimensions(&data).ok_or(Error::Invalid));
+
+			if width > LIMIT || height > LIMIT {
+				return Err(Error::TooBig);
+			}
+
+			cairo::Image::png(&data).ok_or(Error::Invalid)
+		}
+
+		format @ 24 | format @ 32 => {
+			let (width, height) = (command.number(b's', 0), command.number(b'v', 0));
+			let depth           = (format / 8) as usize;
+
+			if width == 0 || height == 0 {
+				return Err(Error::Invalid);
+			}
+
+			if width > LIMIT || height > LIMIT {
+				return Err(Error::TooBig);
+			}
+
+			if data.len() < width as usize * height as usize * depth {
+				return Err(Error::NoData);
+			}
+
+			let mut image = cairo::Image::new(width, height);
+
+			for (i, px) in data.chunks(depth).take((width * height) as usize).enumerate() {
+				let a = if depth == 4 { px[3] } else { 255 };
+
+				// Cairo expects premultiplied alpha.
+				let premultiply = |c: u8| (c as u32 * a as u32 / 255) as u8;
+
+				image.set(i as u32 % width, i as u32 / width,
+					&(premultiply(px[0]), premultiply(px[1]), premultiply(px[2]), a));
+			}
+
+			Ok(image)
+		}
+
+		_ =>
+			Err(Error::Invalid)
+	}
+}
+
+/// Get the width and height from the header of a PNG or JPEG image, without
+/// decoding it.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+	let be16 = |i: usize| (data[i] as u32) << 8 | data[i + 1] as u32;
+	let be32 = |i: usize| be16(i) << 16 | be16(i + 2);
+
+	// The IHDR chunk always comes first.
+	if data.len() >= 24 && &data[.. 8] == b"\x89PNG\r\n\x1A\n" && &data[12 .. 16] == b"IHDR" {
+		return Some((be32(16), be32(20)));
+	}
+
+	if data.len() < 2 || data[0] != 0xFF || data[1] != 0xD8 {
+		return None;
+	}
+
+	// Walk the segments until the frame header, which comes before the scan.
+	let mut i = 2;
+
+	while i + 4 <= data.len() {
+		if data[i] != 0xFF {
+			return None;
+		}
+
+		match data[i + 1] {
+			// Fill byte.
+			0xFF =>
+				i += 1,
+
+			// Markers without a segment.
+			0x01 | 0xD0 ... 0xD7 =>
+				i += 2,
+
+			// Start of frame, except for the DHT, JPG and DAC markers.
+			marker @ 0xC0 ... 0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+				if i + 9 > data.len() {
+					return None;
+				}
+
+				return Some((be16(i + 7), be16(i + 5)));
+			}
+
+			// End of image or start of scan.
+			0xD9 | 0xDA =>
+				return None,
+
+			_ =>
+				i += 2 + be16(i + 2) as usize,
+		}
+	}
+
+	None
+}
+
+/// Get the number of bytes taken by an image.
+fn size(image: &cairo::Image) -> usize {
+	image.width() as usize * image.height() as usize * 4
+}
+
+/// Read the image data from a file, temporary files are removed afterwards.
+fn read(path: &str, offset: u32, size: u32, temporary: bool) -> Result<Vec<u8>, Error> {
+	// Links are resolved first, so the checks apply to the file actually read.
+	let path = try!(fs::canonicalize(path).map_err(|_| Error::BadFile));
+
+	// Only remove files that are clearly meant for us, directly within a
+	// temporary directory.
+	if temporary {
+		let name   = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+		let parent = path.parent();
+		let inside = [env::temp_dir(), PathBuf::from("/tmp"), PathBuf::from("/dev/shm")].iter()
+			.filter_map(|dir| fs::canonicalize(dir).ok())
+			.any(|dir| parent == Some(dir.as_path()));
+
+		if !name.contains("tty-graphics-protocol") || !inside {
+			return Err(Error::BadFile);
+		}
+	}
+
+	// Devices and pipes could block or never end, so only regular files are
+	// read.
+	if !try!(fs::metadata(&path).map_err(|_| Error::BadFile)).is_file() {
+		return Err(Error::BadFile);
+	}
+
+	let     limit = if size != 0 { cmp::min(size as u64, QUOTA as u64) } else { QUOTA as u64 };
+	let mut data  = Vec::new();
+
+	{
+		let mut file = try!(File::open(&path).map_err(|_| Error::BadFile));
+		try!(file.seek(SeekFrom::Start(offset as u64)).map_err(|_| Error::BadFile));
+		try!(file.take(limit).read_to_end(&mut data).map_err(|_| Error::BadFile));
+	}
+
+	if temporary {
+		fs::remove_file(&path).ok();
+	}
+
+	Ok(data)
+}
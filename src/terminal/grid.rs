@@ -16,18 +16,23 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::{Index, IndexMut};
+use std::rc::Rc;
 use std::mem;
+use std::cmp;
 use std::collections::VecDeque;
 
 use itertools::Itertools;
 use util::clamp;
 use terminal::{Cell, Row, Free};
+use terminal::row::{Mark, Size};
+use terminal::graphics::Placement;
 
 #[derive(Debug)]
 pub struct Grid {
 	cols:    u32,
 	rows:    u32,
 	history: usize,
+	reach:   u32,
 
 	free: Free,
 	back: VecDeque<Row>,
@@ -41,6 +46,7 @@ impl Grid {
 			cols:    0,
 			rows:    0,
 			history: history,
+			reach:   0,
 
 			free: Free::new(),
 			back: VecDeque::new(),
@@ -51,6 +57,12 @@ impl Grid {
 		value
 	}
 
+	/// Get the most rows covered by any image placed so far, rows further back
+	/// than this can't reach into the view.
+	pub fn reach(&self) -> u32 {
+		self.reach
+	}
+
 	/// Get the scroll back.
 	pub fn back(&self) -> &VecDeque<Row> {
 		&self.back
@@ -116,6 +128,15 @@ impl Grid {
 						}
 					}
 
+					// Keep the shell integration marks on the first row.
+					let mark   = wrapped.iter().fold(Mark::empty(), |m, r| m | r.mark);
+					let status = wrapped.iter().filter_map(|r| r.status).next();
+					let size   = wrapped.last().unwrap().size;
+
+					// Keep the images on the first row as well.
+					let mut images = wrapped.iter_mut()
+						.flat_map(|r| mem::replace(&mut r.images, Vec::new())).collect::<Vec<_>>();
+
 					// Split the cells into appropriately sized chunks, since we pushed
 					// the rows in reverse order we reverse the iterator.
 					let chunks = mem::replace(&mut wrapped, Vec::new()).into_iter().rev().flat_map(|v| v.inner.into_iter()).chunks(cols as usize);
@@ -123,7 +144,14 @@ impl Grid {
 					// Create new rows with the cells and mark as wrapped if they do wrap
 					// again.
 					for (j, cells) in chunks.into_iter().enumerate() {
-						unwrapped.push(Row { inner: cells.collect(), wrapped: j != 0 });
+						unwrapped.push(Row {
+							inner:   cells.collect(),
+							wrapped: j != 0,
+							mark:    if j == 0 { mark } else { Mark::empty() },
+							status:  if j == 0 { status } else { None },
+							size:    size,
+							images:  if j == 0 { mem::replace(&mut images, Vec::new()) } else { Vec::new() },
+						});
 					}
 
 					// Extend any missing cells from the last row.
@@ -156,12 +184,23 @@ impl Grid {
 
 					if row.len() != cols as usize {
 						let mut wrapped = Vec::new();
+						let     mark    = row.mark;
+						let     status  = row.status;
+						let     size    = row.size;
+						let mut images  = row.images;
 						let     chunks  = row.inner.into_iter().chunks(cols as usize);
 
 						// Create new rows with the cells and mark as wrapped if they do
 						// wrap.
 						for (j, cells) in chunks.into_iter().enumerate() {
-							wrapped.push(Row { inner: cells.collect(), wrapped: j != 0 });
+							wrapped.push(Row {
+								inner:   cells.collect(),
+								wrapped: j != 0,
+								mark:    if j == 0 { mark } else { Mark::empty() },
+								status:  if j == 0 { status } else { None },
+								size:    size,
+								images:  if j == 0 { mem::replace(&mut images, Vec::new()) } else { Vec::new() },
+							});
 						}
 
 						// Extend any missing cells from the last row.
@@ -257,9 +296,29 @@ impl Grid {
 		}
 	}
 
-	/// Scroll the view up by `n`, optionally within the given region.
-	pub fn up(&mut self, n: u32, region: Option<(u32, u32)>) {
-		if let Some(region) = region {
+	/// Scroll the view up by `n`, optionally within the given region and
+	/// columns.
+	pub fn up(&mut self, n: u32, region: Option<(u32, u32)>, columns: Option<(u32, u32)>) {
+		if let Some(columns) = columns {
+			let (top, bottom) = region.unwrap_or((0, self.rows - 1));
+			let n             = clamp(n, 0, bottom - top + 1);
+
+			for y in top ... bottom {
+				for x in columns.0 ... columns.1 {
+					let cell = if y + n <= bottom {
+						self.view[(y + n) as usize][x as usize].clone()
+					}
+					else {
+						self.free.cell()
+					};
+
+					self.view[y as usize][x as usize] = cell;
+				}
+
+				self.clean_edges(y, columns);
+			}
+		}
+		else if let Some(region) = region {
 			let y      = region.0;
 			let n      = clamp(n as u32, 0, region.1 - y + 1);
 			let offset = self.rows - (region.1 + 1);
@@ -283,9 +342,28 @@ impl Grid {
 		self.clean_history();
 	}
 
-	/// Scroll the view down by `n`, optionally within the region.
-	pub fn down(&mut self, n: u32, region: Option<(u32, u32)>) {
-		if let Some(region) = region {
+	/// Scroll the view down by `n`, optionally within the region and columns.
+	pub fn down(&mut self, n: u32, region: Option<(u32, u32)>, columns: Option<(u32, u32)>) {
+		if let Some(columns) = columns {
+			let (top, bottom) = region.unwrap_or((0, self.rows - 1));
+			let n             = clamp(n, 0, bottom - top + 1);
+
+			for y in (top ... bottom).rev() {
+				for x in columns.0 ... columns.1 {
+					let cell = if y >= top + n {
+						self.view[(y - n) as usize][x as usize].clone()
+					}
+					else {
+						self.free.cell()
+					};
+
+					self.view[y as usize][x as usize] = cell;
+				}
+
+				self.clean_edges(y, columns);
+			}
+		}
+		else if let Some(region) = region {
 			let y = region.0;
 			let n = clamp(n as u32, 0, (region.1 - y + 1));
 
@@ -308,29 +386,58 @@ impl Grid {
 		self.clean_history();
 	}
 
-	/// Delete `n` cells starting from the given origin.
-	pub fn delete(&mut self, x: u32, y: u32, n: u32) {
-		let n   = clamp(n, 0, self.cols - x);
-		let row = &mut self.view[y as usize];
+	/// Delete `n` cells starting from the given origin, optionally shifting
+	/// only up to the given right margin.
+	pub fn delete(&mut self, x: u32, y: u32, n: u32, right: Option<u32>) {
+		let cols = right.map(|r| r + 1).unwrap_or(self.cols);
+		let n    = clamp(n, 0, cols - x);
 
-		// The row may contain references, account for them.
-		let mut end = x;
-		for _ in 0 .. n {
-			end += row[end as usize].width();
+		{
+			let row = &mut self.view[y as usize];
 
-			if end >= self.cols {
-				end = self.cols - 1;
-				break;
+			// The row may contain references, account for them.
+			let mut end = x;
+			for _ in 0 .. n {
+				end += row[end as usize].width();
+
+				if end >= cols {
+					end = cols - 1;
+					break;
+				}
+			}
+
+			// Drain the cells and insert empty ones at the end.
+			row.drain(x as usize .. end as usize);
+
+			for _ in x .. end {
+				row.insert((cols - (end - x)) as usize, self.free.cell());
 			}
 		}
 
-		// Drain the cells and insert empty ones at the end.
-		row.drain(x as usize .. end as usize);
-		row.append(&mut vec_deque![self.free.cell(); (end - x) as usize]);
+		if right.is_some() {
+			self.clean_edges(y, (x, cols - 1));
+		}
 	}
 
-	/// Insert `n` empty cells starting from the given origin.
-	pub fn insert(&mut self, x: u32, y: u32, n: u32) {
+	/// Insert `n` empty cells starting from the given origin, optionally
+	/// shifting only up to the given right margin.
+	pub fn insert(&mut self, x: u32, y: u32, n: u32, right: Option<u32>) {
+		if let Some(right) = right {
+			let n = clamp(n, 0, right - x + 1);
+
+			{
+				let row = &mut self.view[y as usize];
+
+				for _ in x .. x + n {
+					row.remove(right as usize);
+					row.insert(x as usize, self.free.cell());
+				}
+			}
+
+			self.clean_edges(y, (x, right));
+			return;
+		}
+
 		let n   = clamp(n as u32, 0, self.cols);
 		let row = &mut self.view[y as usize];
 
@@ -363,10 +470,133 @@ impl Grid {
 		}
 	}
 
+	/// Clean wide characters that got split by changing cells between the
+	/// given columns.
+	pub fn clean_edges(&mut self, y: u32, (left, right): (u32, u32)) {
+		let style = self.free.style();
+		let row   = &mut self.view[y as usize];
+
+		// A wide character before the left edge lost its tail.
+		if left > 0 {
+			let mut x = left - 1;
+
+			while x > 0 && row[x as usize].is_reference() {
+				x -= 1;
+			}
+
+			if !row[x as usize].is_reference() && x + row[x as usize].width() > left {
+				for x in x .. left {
+					row[x as usize].make_empty(style.clone());
+				}
+			}
+		}
+
+		// A wide character lost its head on the left edge.
+		for x in left ... right {
+			if row[x as usize].is_reference() {
+				row[x as usize].make_empty(style.clone());
+			}
+			else {
+				break;
+			}
+		}
+
+		// A wide character lost its tail on the right edge.
+		for x in (left ... right).rev() {
+			if row[x as usize].is_reference() {
+				continue;
+			}
+
+			if x + row[x as usize].width() - 1 > right {
+				for x in x ... right {
+					row[x as usize].make_empty(style.clone());
+				}
+			}
+
+			break;
+		}
+
+		// References past the right edge lost their head.
+		for x in right + 1 .. self.cols {
+			if row[x as usize].is_reference() {
+				row[x as usize].make_empty(style.clone());
+			}
+			else {
+				break;
+			}
+		}
+	}
+
 	/// Mark a row as wrapped.
 	pub fn wrapped(&mut self, y: u32, value: bool) {
 		self.view[y as usize].wrapped = value;
 	}
+
+	/// Add a shell integration mark to a row.
+	pub fn mark(&mut self, y: u32, mark: Mark) {
+		self.view[y as usize].mark.insert(mark);
+	}
+
+	/// Remove any shell integration marks from a row.
+	pub fn unmark(&mut self, y: u32) {
+		self.view[y as usize].mark   = Mark::empty();
+		self.view[y as usize].status = None;
+	}
+
+	/// Set the line size of a row.
+	pub fn size(&mut self, y: u32, size: Size) {
+		self.view[y as usize].size = size;
+	}
+
+	/// Set the exit status of the command that ended on a row.
+	pub fn status(&mut self, y: u32, status: Option<i32>) {
+		self.view[y as usize].status = status;
+	}
+
+	/// Anchor an image to a row.
+	pub fn place(&mut self, y: u32, placement: Rc<Placement>) {
+		self.reach = cmp::max(self.reach, placement.rows());
+		self.view[y as usize].images.push(placement);
+	}
+
+	/// Remove the images matching the predicate, given the row they're anchored
+	/// to, from the view and optionally the scroll back.
+	pub fn unplace<F>(&mut self, history: bool, mut predicate: F) -> Vec<Rc<Placement>>
+		where F: FnMut(i32, &Placement) -> bool
+	{
+		let mut removed = Vec::new();
+		let     back    = if history { self.back.len() } else { 0 };
+
+		let rows = self.back.iter_mut().rev().take(back).enumerate().map(|(i, r)| (-(i as i32) - 1, r))
+			.chain(self.view.iter_mut().enumerate().map(|(i, r)| (i as i32, r)));
+
+		for (y, row) in rows {
+			let mut i = 0;
+
+			while i < row.images.len() {
+				if predicate(y, &row.images[i]) {
+					removed.push(row.images.remove(i));
+				}
+				else {
+					i += 1;
+				}
+			}
+		}
+
+		removed
+	}
+
+	/// Get the images reaching into the view, with the row they're anchored
+	/// to.
+	pub fn images(&self) -> Vec<(i32, Rc<Placement>)> {
+		let back = self.back.iter().rev().take(self.reach as usize).enumerate().map(|(i, r)| (-(i as i32) - 1, r));
+		let view = self.view.iter().enumerate().map(|(i, r)| (i as i32, r));
+
+		back.chain(view)
+			.flat_map(|(y, row)| row.images.iter().map(move |p| (y, p.clone())))
+			.filter(|&(y, ref p)| y + p.rows() as i32 > 0)
+			.collect()
+	}
 }
 
 impl Index<(u32, u32)> for Grid {
@@ -0,0 +1,216 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+
+use base64;
+use picto::read;
+use picto::color::Rgba;
+use sys::cairo;
+use super::graphics::{self, LIMIT};
+
+/// The maximum drawn size, in screens.
+const SCREENS: u32 = 4;
+
+/// An image sent with the iTerm2 inline image protocol.
+#[derive(Debug)]
+pub struct Inline {
+	width:    Option<Dimension>,
+	height:   Option<Dimension>,
+	preserve: bool,
+	image:    cairo::Image,
+}
+
+/// A requested width or height.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Dimension {
+	Cells(u32),
+	Pixels(u32),
+	Percent(u32),
+}
+
+impl Dimension {
+	/// Parse the dimension, `None` if it's `auto` or invalid.
+	fn parse(value: &str) -> Option<Self> {
+		if value.ends_with("px") {
+			value[.. value.len() - 2].parse().ok().map(Dimension::Pixels)
+		}
+		else if value.ends_with('%') {
+			value[.. value.len() - 1].parse().ok().map(Dimension::Percent)
+		}
+		else {
+			value.parse().ok().map(Dimension::Cells)
+		}
+	}
+
+	/// Get the size in pixels, given the size of a cell and of the screen, it's
+	/// never bigger than `SCREENS` screens.
+	fn pixels(&self, cell: u32, screen: u32) -> u32 {
+		let pixels = match *self {
+			Dimension::Cells(n)   => n as u64 * cell as u64,
+			Dimension::Pixels(n)  => n as u64,
+			Dimension::Percent(n) => screen as u64 * cmp::min(n, 100) as u64 / 100,
+		};
+
+		cmp::min(pixels, screen as u64 * SCREENS as u64) as u32
+	}
+}
+
+impl Inline {
+	/// Parse the arguments and decode the image, `None` if the file isn't meant
+	/// to be shown inline or can't be decoded.
+	pub fn parse(string: &str) -> Option<Self> {
+		let mut parts     = string.splitn(2, ':');
+		let     arguments = try!(option parts.next());
+		let     data      = try!(option parts.next());
+
+		let mut width    = None;
+		let mut height   = None;
+		let mut preserve = true;
+		let mut inline   = false;
+
+		for argument in arguments.split(';') {
+			let mut parts = argument.splitn(2, '=');
+
+			match (parts.next(), parts.next()) {
+				(Some("width"), Some(value)) =>
+					width = Dimension::parse(value),
+
+				(Some("height"), Some(value)) =>
+					height = Dimension::parse(value),
+
+				(Some("preserveAspectRatio"), Some(value)) =>
+					preserve = value != "0",
+
+				(Some("inline"), Some(value)) =>
+					inline = value == "1",
+
+				_ => ()
+			}
+		}
+
+		// Downloads aren't supported.
+		if !inline {
+			return None;
+		}
+
+		let data = try!(option base64::decode(data).ok());
+
+		// Images are held as they are, so they're kept as small as transmitted
+		// ones, the size is checked before decoding.
+		let (width, height) = try!(option graphics::dimensions(&data));
+
+		if width > LIMIT || height > LIMIT {
+			return None;
+		}
+
+		let buffer = try!(option read::from_memory::<Rgba, u8, _>(data).ok());
+
+		if buffer.width() != width || buffer.height() != height {
+			return None;
+		}
+
+		let mut image = cairo::Image::new(buffer.width(), buffer.height());
+
+		for y in 0 .. buffer.height() {
+			for x in 0 .. buffer.width() {
+				let (r, g, b, a): (u8, u8, u8, u8) = buffer.get(x, y).to_pixel();
+
+				// Cairo expects premultiplied alpha.
+				let premultiply = |c: u8| (c as u32 * a as u32 / 255) as u8;
+				image.set(x, y, &(premultiply(r), premultiply(g), premultiply(b), a));
+			}
+		}
+
+		Some(Inline {
+			width:    width,
+			height:   height,
+			preserve: preserve,
+			image:    image,
+		})
+	}
+
+	/// Get the drawn size in pixels, images without a requested size are shrunk
+	/// to fit the screen width, and no side is bigger than `SCREENS` screens.
+	pub fn size(&self, cell: (u32, u32), screen: (u32, u32)) -> (u32, u32) {
+		let (iw, ih) = (self.image.width(), self.image.height());
+
+		if iw == 0 || ih == 0 {
+			return (0, 0);
+		}
+
+		// Scale a side by the ratio of the other two.
+		let scale = |value: u32, to: u32, from: u32|
+			cmp::max(1, value as u64 * to as u64 / from as u64);
+
+		let width  = self.width.map(|d| d.pixels(cell.0, screen.0));
+		let height = self.height.map(|d| d.pixels(cell.1, screen.1));
+
+		let (width, height) = match (width, height) {
+			(None, None) if iw > screen.0 =>
+				(screen.0 as u64, scale(ih, screen.0, iw)),
+
+			(None, None) =>
+				(iw as u64, ih as u64),
+
+			(Some(w), None) =>
+				(w as u64, scale(ih, w, iw)),
+
+			(None, Some(h)) =>
+				(scale(iw, h, ih), h as u64),
+
+			// Fit the image within the box, keeping the aspect ratio.
+			(Some(w), Some(h)) if self.preserve => {
+				if w as u64 * ih as u64 <= h as u64 * iw as u64 {
+					(w as u64, scale(ih, w, iw))
+				}
+				else {
+					(scale(iw, h, ih), h as u64)
+				}
+			}
+
+			(Some(w), Some(h)) =>
+				(w as u64, h as u64),
+		};
+
+		(cmp::min(width, screen.0 as u64 * SCREENS as u64) as u32,
+		 cmp::min(height, screen.1 as u64 * SCREENS as u64) as u32)
+	}
+
+	/// Draw the scaled image into a grid of cell sized buffers, clipped at the
+	/// given number of columns.
+	pub fn draw(&self, cell: (u32, u32), screen: (u32, u32), columns: u32) -> Vec<Vec<cairo::Image>> {
+		let (width, height) = self.size(cell, screen);
+
+		if width == 0 || height == 0 {
+			return Vec::new();
+		}
+
+		let columns    = cmp::min(columns, (width + cell.0 - 1) / cell.0);
+		let rows       = (height + cell.1 - 1) / cell.1;
+		let mut canvas = cairo::Image::new(columns * cell.0, rows * cell.1);
+
+		{
+			let mut context = cairo::Context::new(&canvas.surface());
+			context.scale(width as f64 / self.image.width() as f64, height as f64 / self.image.height() as f64);
+			context.image(&self.image, 0.0, 0.0);
+		}
+
+		(0 .. rows).map(|y| (0 .. columns).map(|x|
+			canvas.crop(x * cell.0, y * cell.1, cell.0, cell.1)).collect()).collect()
+	}
+}
@@ -0,0 +1,201 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{self, Write};
+
+use platform::key::{self, Key, Value, Button, Keypad, State};
+
+bitflags! {
+	pub flags Keyboard: u8 {
+		const DISAMBIGUATE = 1 << 0,
+		const EVENTS       = 1 << 1,
+		const ALL          = 1 << 3,
+	}
+}
+
+/// Encode a key with the progressive enhancement keyboard protocol, returns
+/// `false` if the key has to go through the legacy encoding instead.
+pub fn progressive<O: Write>(key: &Key, flags: Keyboard, mut output: O) -> io::Result<bool> {
+	if flags.is_empty() {
+		return Ok(false);
+	}
+
+	let modifier = modifiers(key, flags.contains(ALL));
+	let plain    = !key.modifier().intersects(key::ALT | key::CTRL | key::LOGO);
+	let event    = match key.state() {
+		State::Press =>
+			1,
+
+		State::Repeat if flags.contains(EVENTS) =>
+			2,
+
+		State::Repeat =>
+			1,
+
+		State::Release if flags.contains(EVENTS) =>
+			3,
+
+		State::Release =>
+			return Ok(false),
+	};
+
+	let (number, terminator) = match *key.value() {
+		// Text is sent as is, unless it's modified or all keys are requested.
+		Value::Char(ref string) => {
+			let mut chars = string.chars();
+
+			match (chars.next(), chars.next()) {
+				(Some(ch), None) if !plain || flags.contains(ALL) =>
+					(ch.to_lowercase().next().unwrap_or(ch) as u32, b'u'),
+
+				_ =>
+					return Ok(false),
+			}
+		}
+
+		// These are kept as is when unmodified, so a shell stays usable if the
+		// application that enabled the protocol dies.
+		Value::Button(Button::Enter) |
+		Value::Button(Button::Tab) |
+		Value::Button(Button::Backspace) if modifier == 1 && !flags.contains(ALL) =>
+			return Ok(false),
+
+		Value::Button(Button::Escape)    => (27, b'u'),
+		Value::Button(Button::Enter)     => (13, b'u'),
+		Value::Button(Button::Tab)       => (9, b'u'),
+		Value::Button(Button::Backspace) => (127, b'u'),
+
+		Value::Button(Button::Insert)   => (2, b'~'),
+		Value::Button(Button::Delete)   => (3, b'~'),
+		Value::Button(Button::PageUp)   => (5, b'~'),
+		Value::Button(Button::PageDown) => (6, b'~'),
+		Value::Button(Button::Up)       => (1, b'A'),
+		Value::Button(Button::Down)     => (1, b'B'),
+		Value::Button(Button::Right)    => (1, b'C'),
+		Value::Button(Button::Left)     => (1, b'D'),
+		Value::Button(Button::Home)     => (1, b'H'),
+		Value::Button(Button::End)      => (1, b'F'),
+		Value::Button(Button::Menu)     => (57363, b'u'),
+
+		Value::Button(Button::F(1))  => (1, b'P'),
+		Value::Button(Button::F(2))  => (1, b'Q'),
+		Value::Button(Button::F(3))  => (13, b'~'),
+		Value::Button(Button::F(4))  => (1, b'S'),
+		Value::Button(Button::F(5))  => (15, b'~'),
+		Value::Button(Button::F(6))  => (17, b'~'),
+		Value::Button(Button::F(7))  => (18, b'~'),
+		Value::Button(Button::F(8))  => (19, b'~'),
+		Value::Button(Button::F(9))  => (20, b'~'),
+		Value::Button(Button::F(10)) => (21, b'~'),
+		Value::Button(Button::F(11)) => (23, b'~'),
+		Value::Button(Button::F(12)) => (24, b'~'),
+		Value::Button(Button::F(n))  => (57376 + n as u32 - 13, b'u'),
+
+		Value::Keypad(Keypad::Number(n)) => (57399 + n as u32, b'u'),
+		Value::Keypad(Keypad::Decimal)   => (57409, b'u'),
+		Value::Keypad(Keypad::Divide)    => (57410, b'u'),
+		Value::Keypad(Keypad::Multiply)  => (57411, b'u'),
+		Value::Keypad(Keypad::Subtract)  => (57412, b'u'),
+		Value::Keypad(Keypad::Add)       => (57413, b'u'),
+		Value::Keypad(Keypad::Enter)     => (57414, b'u'),
+		Value::Keypad(Keypad::Left)      => (57417, b'u'),
+		Value::Keypad(Keypad::Right)     => (57418, b'u'),
+		Value::Keypad(Keypad::Up)        => (57419, b'u'),
+		Value::Keypad(Keypad::Down)      => (57420, b'u'),
+		Value::Keypad(Keypad::PageUp)    => (57421, b'u'),
+		Value::Keypad(Keypad::PageDown)  => (57422, b'u'),
+		Value::Keypad(Keypad::Home)      => (57423, b'u'),
+		Value::Keypad(Keypad::End)       => (57424, b'u'),
+		Value::Keypad(Keypad::Insert)    => (57425, b'u'),
+		Value::Keypad(Keypad::Begin)     => (57427, b'u'),
+	};
+
+	if modifier == 1 && event == 1 {
+		if number == 1 {
+			try!(write!(output, "\x1B[{}", terminator as char));
+		}
+		else {
+			try!(write!(output, "\x1B[{}{}", number, terminator as char));
+		}
+	}
+	else if event == 1 {
+		try!(write!(output, "\x1B[{};{}{}", number, modifier, terminator as char));
+	}
+	else {
+		try!(write!(output, "\x1B[{};{}:{}{}", number, modifier, event, terminator as char));
+	}
+
+	Ok(true)
+}
+
+/// Encode a modified key like xterm does with `modifyOtherKeys` set to 2,
+/// returns `false` if the key has to go through the legacy encoding instead.
+pub fn modified<O: Write>(key: &Key, mut output: O) -> io::Result<bool> {
+	let modifier = modifiers(key, false);
+
+	// Shift alone is already part of the text.
+	if modifier == 1 || key.modifier() == key::SHIFT {
+		return Ok(false);
+	}
+
+	let code = match *key.value() {
+		Value::Char(ref string) if string.chars().count() == 1 =>
+			string.chars().next().unwrap() as u32,
+
+		Value::Button(Button::Escape)    => 27,
+		Value::Button(Button::Enter)     => 13,
+		Value::Button(Button::Tab)       => 9,
+		Value::Button(Button::Backspace) => 127,
+
+		_ =>
+			return Ok(false),
+	};
+
+	try!(write!(output, "\x1B[27;{};{}~", modifier, code));
+	Ok(true)
+}
+
+/// The modifier parameter, optionally including the locks.
+fn modifiers(key: &Key, locks: bool) -> u32 {
+	let mut value = 0;
+
+	if key.modifier().contains(key::SHIFT) {
+		value |= 1;
+	}
+
+	if key.modifier().contains(key::ALT) {
+		value |= 2;
+	}
+
+	if key.modifier().contains(key::CTRL) {
+		value |= 4;
+	}
+
+	if key.modifier().contains(key::LOGO) {
+		value |= 8;
+	}
+
+	if locks && key.lock().contains(key::CAPS) {
+		value |= 64;
+	}
+
+	if locks && key.lock().contains(key::NUM) {
+		value |= 128;
+	}
+
+	value + 1
+}
@@ -0,0 +1,89 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::rc::{Rc, Weak};
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
+
+/// An explicit hyperlink.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Link {
+	id:  Option<String>,
+	uri: String,
+}
+
+impl Link {
+	/// Get the optional ID.
+	pub fn id(&self) -> Option<&str> {
+		self.id.as_ref().map(AsRef::as_ref)
+	}
+
+	/// Get the URI.
+	pub fn uri(&self) -> &str {
+		&self.uri
+	}
+
+	/// Check if two links are the same interned link.
+	pub fn same(a: Option<&Rc<Link>>, b: Option<&Rc<Link>>) -> bool {
+		match (a, b) {
+			(Some(a), Some(b)) =>
+				&**a as *const Link == &**b as *const Link,
+
+			(None, None) =>
+				true,
+
+			_ =>
+				false
+		}
+	}
+}
+
+/// Interner for hyperlinks.
+///
+/// Links with an ID are shared between uses with the same ID and URI, links
+/// without one are always unique.
+#[derive(Default, Debug)]
+pub struct Links {
+	inner: HashMap<(String, String), Weak<Link>, BuildHasherDefault<FnvHasher>>,
+}
+
+impl Links {
+	/// Get the interned link for the given ID and URI.
+	pub fn get(&mut self, id: Option<&str>, uri: &str) -> Rc<Link> {
+		let id = if let Some(id) = id {
+			id
+		}
+		else {
+			return Rc::new(Link { id: None, uri: uri.into() });
+		};
+
+		let key = (id.to_owned(), uri.to_owned());
+
+		if let Some(link) = self.inner.get(&key).and_then(Weak::upgrade) {
+			return link;
+		}
+
+		// Drop links that aren't referenced by any cell anymore.
+		self.inner.retain(|_, link| link.upgrade().is_some());
+
+		let link = Rc::new(Link { id: Some(key.0.clone()), uri: key.1.clone() });
+		self.inner.insert(key, Rc::downgrade(&link));
+
+		link
+	}
+}
@@ -21,7 +21,10 @@ pub use self::iter::Iter;
 pub mod cell;
 pub use self::cell::Cell;
 
-mod row;
+pub mod link;
+pub use self::link::{Link, Links};
+
+pub mod row;
 pub use self::row::Row;
 
 mod free;
@@ -42,11 +45,31 @@ pub use self::grid::Grid;
 mod tabs;
 pub use self::tabs::Tabs;
 
+mod palette;
+pub use self::palette::Palette;
+
 mod input;
 pub use self::input::Input;
 
+mod charset;
+
+pub mod keyboard;
+pub use self::keyboard::Keyboard;
+
+pub mod sequence;
+pub use self::sequence::Sequence;
+
 mod sixel;
 pub use self::sixel::Sixel;
 
+mod regis;
+pub use self::regis::Regis;
+
+pub mod graphics;
+pub use self::graphics::Graphics;
+
+mod inline;
+pub use self::inline::Inline;
+
 mod terminal;
 pub use self::terminal::Terminal;
@@ -39,11 +39,26 @@ bitflags! {
 		                     MOUSE_MOTION.bits |
 		                     MOUSE_X10.bits    |
 		                     MOUSE_MANY.bits,
+
+		const MOUSE_UTF8   = 1 << 19,
+		const MOUSE_URXVT  = 1 << 20,
+		const MOUSE_PIXELS = 1 << 21,
+		const MOUSE_FORMAT = MOUSE_UTF8.bits  |
+		                     MOUSE_SGR.bits   |
+		                     MOUSE_URXVT.bits |
+		                     MOUSE_PIXELS.bits,
+
+		const ALTERNATE    = 1 << 17,
+		const MARGINS      = 1 << 18,
+		const SYNCHRONIZED = 1 << 22,
+
+		const SIXEL_DISPLAY = 1 << 23,
+		const SIXEL_PRIVATE = 1 << 24,
 	}
 }
 
 impl Default for Mode {
 	fn default() -> Self {
-		WRAP | UTF8
+		WRAP | UTF8 | SIXEL_PRIVATE
 	}
 }
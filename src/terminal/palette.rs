@@ -0,0 +1,80 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use picto::color::Rgba;
+use config::{self, Config};
+
+/// The live color palette of a terminal, starting from the configuration and
+/// changeable at runtime.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Palette {
+	config: Arc<Config>,
+
+	table:      config::Color,
+	foreground: Rgba<f64>,
+	background: Rgba<f64>,
+}
+
+impl Palette {
+	/// Create a new palette from the configuration.
+	pub fn new(config: Arc<Config>) -> Self {
+		Palette {
+			table:      config.color().clone(),
+			foreground: *config.style().color().foreground(),
+			background: *config.style().color().background(),
+
+			config: config,
+		}
+	}
+
+	/// Get the indexed color.
+	pub fn get(&self, index: u8) -> &Rgba<f64> {
+		self.table.get(index)
+	}
+
+	/// Change the indexed color.
+	pub fn set(&mut self, index: u8, color: Rgba<f64>) {
+		self.table.set(index, color);
+	}
+
+	/// Reset the indexed color to the configured one.
+	pub fn reset(&mut self, index: u8) {
+		self.table.set(index, *self.config.color().get(index));
+	}
+
+	/// Get the default foreground color.
+	pub fn foreground(&self) -> &Rgba<f64> {
+		&self.foreground
+	}
+
+	/// Change the default foreground color, `None` resets it.
+	pub fn set_foreground(&mut self, color: Option<Rgba<f64>>) {
+		self.foreground = color.unwrap_or(*self.config.style().color().foreground());
+	}
+
+	/// Get the default background color.
+	pub fn background(&self) -> &Rgba<f64> {
+		&self.background
+	}
+
+	/// Change the default background color, `None` resets it.
+	pub fn set_background(&mut self, color: Option<Rgba<f64>>) {
+		self.background = color.unwrap_or(*self.config.style().color().background());
+	}
+}
@@ -0,0 +1,1007 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::f64;
+use std::cmp;
+use std::mem;
+
+use picto::color::{Rgba, Hsl, RgbHue};
+use sys::cairo;
+use super::sixel::COLORS;
+use super::terminal::STRING;
+
+/// ReGIS commands, they're collected until the end of the string and then
+/// drawn all at once.
+#[derive(Debug)]
+pub struct Regis {
+	buffer:     Vec<u8>,
+	foreground: Rgba<f64>,
+}
+
+impl Regis {
+	pub fn new(foreground: &Rgba<f64>) -> Self {
+		Regis {
+			buffer:     Vec::new(),
+			foreground: *foreground,
+		}
+	}
+
+	/// Add more commands, anything past the string limit is dropped.
+	pub fn feed(&mut self, input: &[u8]) {
+		let length = cmp::min(input.len(), STRING.saturating_sub(self.buffer.len()));
+		self.buffer.extend_from_slice(&input[.. length]);
+	}
+
+	/// Draw the commands on the canvas, returns whether the screen was erased.
+	pub fn draw(self, canvas: &mut cairo::Image) -> bool {
+		let size = (canvas.width() as f64, canvas.height() as f64);
+
+		let mut painter = Painter {
+			input:   &self.buffer,
+			offset:  0,
+			context: cairo::Context::new(&canvas.surface()),
+
+			size:     size,
+			screen:   ((0.0, 0.0), (799.0, 479.0)),
+			position: (0.0, 0.0),
+			stack:    Vec::new(),
+			curve:    None,
+
+			colors:     COLORS.iter().map(|&(r, g, b)| Rgba::new_u8(r, g, b, 255)).collect(),
+			background: Rgba::new(0.0, 0.0, 0.0, 0.0),
+			text:       20.0,
+			write:      Write {
+				color:      self.foreground,
+				mode:       Mode::Overlay,
+				negative:   false,
+				multiplier: 1.0,
+			},
+
+			filling: false,
+			path:    false,
+			erased:  false,
+		};
+
+		painter.run(false);
+		painter.erased
+	}
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Mode {
+	Overlay,
+	Replace,
+	Erase,
+	Complement,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Write {
+	color:      Rgba<f64>,
+	mode:       Mode,
+	negative:   bool,
+	multiplier: f64,
+}
+
+type Coordinate = (Option<(f64, bool)>, Option<(f64, bool)>);
+
+struct Painter<'a> {
+	input:   &'a [u8],
+	offset:  usize,
+	context: cairo::Context,
+
+	size:     (f64, f64),
+	screen:   ((f64, f64), (f64, f64)),
+	position: (f64, f64),
+	stack:    Vec<(bool, (f64, f64))>,
+	curve:    Option<(bool, Vec<(f64, f64)>)>,
+
+	colors:     Vec<Rgba<f64>>,
+	background: Rgba<f64>,
+	text:       f64,
+	write:      Write,
+
+	filling: bool,
+	path:    bool,
+	erased:  bool,
+}
+
+impl<'a> Painter<'a> {
+	/// Run commands until the end of the input, or the end of the group if
+	/// nested.
+	fn run(&mut self, nested: bool) {
+		while let Some(byte) = self.next() {
+			match upper(byte) {
+				b')' if nested =>
+					return,
+
+				b'P' =>
+					self.position(),
+
+				b'V' =>
+					self.vector(),
+
+				b'C' =>
+					self.curve(),
+
+				b'T' =>
+					self.text(),
+
+				b'S' =>
+					self.screen(),
+
+				b'F' =>
+					self.fill(),
+
+				b'W' => {
+					if self.peek() == Some(b'(') {
+						self.offset += 1;
+						self.controls();
+					}
+				}
+
+				// Macrographs are not supported, skip their definitions.
+				b'@' => {
+					if self.peek() == Some(b':') {
+						while let Some(byte) = self.raw() {
+							if byte == b'@' && self.input.get(self.offset) == Some(&b';') {
+								self.offset += 1;
+								break;
+							}
+						}
+					}
+					else {
+						self.raw();
+					}
+				}
+
+				// Reports and character sets are not supported.
+				b'R' | b'L' => {
+					while self.peek().map(|b| b == b'(' || b == b'[' || b == b'\'' || b == b'"').unwrap_or(false) {
+						self.argument();
+					}
+				}
+
+				_ => ()
+			}
+		}
+	}
+
+	/// The position command.
+	fn position(&mut self) {
+		let saved = self.write;
+
+		loop {
+			match self.peek() {
+				Some(b'[') => {
+					self.offset += 1;
+					let target = self.coordinate();
+					self.position = self.resolve(target);
+				}
+
+				Some(digit @ b'0' ... b'7') => {
+					self.offset += 1;
+					let (x, y) = direction(digit);
+					self.position.0 += x * self.write.multiplier;
+					self.position.1 += y * self.write.multiplier;
+				}
+
+				Some(b'(') => {
+					self.offset += 1;
+					self.options(|this, option| this.stack(option, false));
+				}
+
+				_ => break
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// The vector command.
+	fn vector(&mut self) {
+		let saved = self.write;
+
+		loop {
+			match self.peek() {
+				Some(b'[') => {
+					self.offset += 1;
+					let target = self.coordinate();
+					let target = self.resolve(target);
+					self.segment(target);
+				}
+
+				Some(digit @ b'0' ... b'7') => {
+					self.offset += 1;
+					let (x, y) = direction(digit);
+					let target = (
+						self.position.0 + x * self.write.multiplier,
+						self.position.1 + y * self.write.multiplier);
+
+					self.segment(target);
+				}
+
+				Some(b'(') => {
+					self.offset += 1;
+					self.options(|this, option| this.stack(option, true));
+				}
+
+				_ => break
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// The curve command, for circles, arcs and curves through points.
+	fn curve(&mut self) {
+		let     saved  = self.write;
+		let mut arc    = None;
+		let mut center = false;
+
+		loop {
+			match self.peek() {
+				Some(b'[') => {
+					self.offset += 1;
+					let point = self.coordinate();
+					let point = self.resolve(point);
+
+					if let Some((_, ref mut points)) = self.curve {
+						points.push(point);
+						continue;
+					}
+
+					let (origin, start) = if center {
+						(point, self.position)
+					}
+					else {
+						(self.position, point)
+					};
+
+					let radius = ((start.0 - origin.0).powi(2) + (start.1 - origin.1).powi(2)).sqrt();
+					let from   = (start.1 - origin.1).atan2(start.0 - origin.0);
+
+					// Positive degrees go counterclockwise on screen.
+					let to = if let Some(degrees) = arc {
+						from - degrees.to_radians()
+					}
+					else {
+						from + 2.0 * f64::consts::PI
+					};
+
+					self.arc(origin, radius, from, to);
+				}
+
+				Some(b'(') => {
+					self.offset += 1;
+					self.options(|this, option| {
+						match option {
+							b'A' => {
+								arc = this.number().map(|(value, _)| value);
+							}
+
+							b'C' => {
+								center = true;
+							}
+
+							b'B' | b'S' => {
+								let position = this.position;
+								this.curve = Some((option == b'B', vec![position]));
+							}
+
+							b'E' => {
+								if let Some((closed, points)) = this.curve.take() {
+									this.spline(closed, &points);
+
+									this.position = if closed {
+										points[0]
+									}
+									else {
+										*points.last().unwrap()
+									};
+								}
+							}
+
+							_ =>
+								this.stack(option, true)
+						}
+					});
+				}
+
+				_ => break
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// The text command.
+	fn text(&mut self) {
+		let saved = self.write;
+
+		loop {
+			match self.peek() {
+				Some(b'\'') | Some(b'"') => {
+					let string = self.string();
+					self.show(&string);
+				}
+
+				Some(b'[') => {
+					self.offset += 1;
+					self.coordinate();
+				}
+
+				Some(b'(') => {
+					self.offset += 1;
+					self.options(|this, option| {
+						match option {
+							// Either a standard size, or a size in logical units.
+							b'S' => {
+								if this.peek() == Some(b'[') {
+									this.offset += 1;
+
+									if let (_, Some((height, _))) = this.coordinate() {
+										this.text = height.abs();
+									}
+								}
+								else if let Some((size, _)) = this.number() {
+									this.text = match size.abs() as u32 {
+										0 => 10.0,
+										1 => 20.0,
+										n => 15.0 * n as f64,
+									};
+								}
+							}
+
+							b'W' =>
+								this.stack(option, false),
+
+							_ =>
+								this.argument(),
+						}
+					});
+				}
+
+				_ => break
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// The screen command.
+	fn screen(&mut self) {
+		loop {
+			match self.peek() {
+				Some(b'[') => {
+					self.offset += 1;
+					self.coordinate();
+				}
+
+				Some(b'(') => {
+					self.offset += 1;
+					self.options(|this, option| {
+						match option {
+							b'E' =>
+								this.erase(),
+
+							b'I' => {
+								if let Some(color) = this.color() {
+									this.background = color;
+								}
+							}
+
+							// Change the logical screen addressing.
+							b'A' => {
+								let mut corners = Vec::new();
+
+								while this.peek() == Some(b'[') {
+									this.offset += 1;
+									corners.push(this.coordinate());
+								}
+
+								let ((x1, y1), (x2, y2)) = this.screen;
+								if let Some(&(x, y)) = corners.get(0) {
+									this.screen.0 = (x.map(|v| v.0).unwrap_or(x1), y.map(|v| v.0).unwrap_or(y1));
+								}
+
+								if let Some(&(x, y)) = corners.get(1) {
+									this.screen.1 = (x.map(|v| v.0).unwrap_or(x2), y.map(|v| v.0).unwrap_or(y2));
+								}
+							}
+
+							// Change entries in the color map.
+							b'M' => {
+								while let Some((index, _)) = this.number() {
+									if let Some(color) = this.color() {
+										if let Some(entry) = this.colors.get_mut(index.abs() as usize) {
+											*entry = color;
+										}
+									}
+								}
+							}
+
+							_ =>
+								this.argument(),
+						}
+					});
+				}
+
+				_ => break
+			}
+		}
+	}
+
+	/// The fill command, the nested commands build a path which is then filled.
+	fn fill(&mut self) {
+		if self.peek() != Some(b'(') {
+			return;
+		}
+
+		self.offset += 1;
+
+		// Fills don't nest, so a nested one is skipped instead of recursing.
+		if self.filling {
+			self.group(b')');
+			return;
+		}
+
+		self.context.new_path();
+
+		self.filling = true;
+		self.path    = false;
+		self.run(true);
+		self.filling = false;
+
+		if self.path {
+			self.source();
+			self.context.close_path();
+			self.context.fill();
+		}
+	}
+
+	/// The write controls, after the opening parenthesis.
+	fn controls(&mut self) {
+		self.options(|this, option| {
+			match option {
+				b'I' => {
+					if let Some(color) = this.color() {
+						this.write.color = color;
+					}
+				}
+
+				b'V' => this.write.mode = Mode::Overlay,
+				b'R' => this.write.mode = Mode::Replace,
+				b'E' => this.write.mode = Mode::Erase,
+				b'C' => this.write.mode = Mode::Complement,
+
+				b'N' => {
+					this.write.negative = this.number().map(|(v, _)| v != 0.0).unwrap_or(false);
+				}
+
+				b'M' => {
+					if let Some((value, _)) = this.number() {
+						this.write.multiplier = value.abs().max(1.0);
+					}
+				}
+
+				_ =>
+					this.argument(),
+			}
+		});
+	}
+
+	/// Handle the options shared by positions and vectors.
+	fn stack(&mut self, option: u8, vector: bool) {
+		match option {
+			b'B' => {
+				let position = self.position;
+				self.stack.push((true, position));
+			}
+
+			b'S' => {
+				let position = self.position;
+				self.stack.push((false, position));
+			}
+
+			// Going back to the position is only done for bounded stacks.
+			b'E' => {
+				if let Some((true, position)) = self.stack.pop() {
+					if vector {
+						self.segment(position);
+					}
+					else {
+						self.position = position;
+					}
+				}
+			}
+
+			b'W' => {
+				if self.peek() == Some(b'(') {
+					self.offset += 1;
+					self.controls();
+				}
+			}
+
+			_ =>
+				self.argument(),
+		}
+	}
+
+	/// Parse options until the closing parenthesis.
+	fn options<F: FnMut(&mut Self, u8)>(&mut self, mut handler: F) {
+		while let Some(byte) = self.next() {
+			match upper(byte) {
+				b')' =>
+					break,
+
+				b'(' =>
+					self.group(b')'),
+
+				b'[' =>
+					self.group(b']'),
+
+				option @ b'A' ... b'Z' => {
+					handler(self, option);
+				}
+
+				_ => ()
+			}
+		}
+	}
+
+	/// Draw a line to the given position.
+	fn segment(&mut self, target: (f64, f64)) {
+		let from = self.pixel(self.position);
+		let to   = self.pixel(target);
+
+		if self.filling {
+			if !self.path {
+				self.context.move_to(from.0, from.1);
+				self.path = true;
+			}
+
+			self.context.line_to(to.0, to.1);
+		}
+		else if from == to {
+			let width = self.source();
+
+			self.context.rectangle(to.0 - width / 2.0, to.1 - width / 2.0, width, width);
+			self.context.fill();
+		}
+		else {
+			self.source();
+
+			self.context.new_path();
+			self.context.move_to(from.0, from.1);
+			self.context.line_to(to.0, to.1);
+			self.context.stroke();
+		}
+
+		self.position = target;
+	}
+
+	/// Draw an arc in logical units.
+	fn arc(&mut self, center: (f64, f64), radius: f64, from: f64, to: f64) {
+		let (x, y)   = self.pixel(center);
+		let (sx, sy) = self.scale();
+
+		if !self.filling {
+			self.context.new_path();
+		}
+
+		self.context.save();
+		self.context.translate(x, y);
+		self.context.scale(sx, sy);
+		self.context.arc(0.0, 0.0, radius, from, to);
+		self.context.restore();
+
+		if self.filling {
+			self.path = true;
+		}
+		else {
+			self.source();
+			self.context.stroke();
+		}
+	}
+
+	/// Draw a smooth curve through the given points.
+	fn spline(&mut self, closed: bool, points: &[(f64, f64)]) {
+		if points.len() < 2 {
+			return;
+		}
+
+		let points = points.iter().map(|&p| self.pixel(p)).collect::<Vec<_>>();
+		let length = points.len() as isize;
+		let point  = |i: isize| if closed {
+			points[((i % length + length) % length) as usize]
+		}
+		else {
+			points[cmp::min(cmp::max(i, 0), length - 1) as usize]
+		};
+
+		if !self.filling {
+			self.context.new_path();
+		}
+
+		if !self.filling || !self.path {
+			self.context.move_to(points[0].0, points[0].1);
+			self.path = true;
+		}
+
+		// Catmull-Rom segments turned into Bézier curves.
+		for i in 0 .. if closed { length } else { length - 1 } {
+			let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+
+			self.context.curve_to(
+				(p1.0 + (p2.0 - p0.0) / 6.0, p1.1 + (p2.1 - p0.1) / 6.0),
+				(p2.0 - (p3.0 - p1.0) / 6.0, p2.1 - (p3.1 - p1.1) / 6.0),
+				p2);
+		}
+
+		if !self.filling {
+			self.source();
+			self.context.stroke();
+		}
+	}
+
+	/// Draw text at the current position.
+	fn show(&mut self, text: &str) {
+		let (x, y)   = self.pixel(self.position);
+		let (sx, sy) = self.scale();
+		let size     = self.text * sy;
+
+		self.source();
+		self.context.font_size(size);
+		self.context.move_to(x, y + size * 0.8);
+
+		let (advance, _) = self.context.text(text);
+		self.position.0 += advance / sx;
+	}
+
+	/// Erase the whole screen with the background, which is transparent unless
+	/// changed.
+	fn erase(&mut self) {
+		let background = self.background;
+
+		self.context.operator(cairo::Operator::Source);
+		self.context.rgba(&background);
+		self.context.paint();
+
+		self.erased = true;
+	}
+
+	/// Prepare the source for drawing, returns the line width.
+	fn source(&mut self) -> f64 {
+		let (mut color, mut background) = (self.write.color, self.background);
+
+		if self.write.negative {
+			mem::swap(&mut color, &mut background);
+		}
+
+		match self.write.mode {
+			Mode::Overlay => {
+				self.context.operator(cairo::Operator::Over);
+				self.context.rgba(&color);
+			}
+
+			Mode::Replace => {
+				self.context.operator(cairo::Operator::Source);
+				self.context.rgba(&color);
+			}
+
+			Mode::Erase => {
+				self.context.operator(cairo::Operator::Source);
+				self.context.rgba(&background);
+			}
+
+			Mode::Complement => {
+				self.context.operator(cairo::Operator::Difference);
+				self.context.rgba(&Rgba::new(1.0, 1.0, 1.0, 1.0));
+			}
+		}
+
+		let (sx, sy) = self.scale();
+		let width    = sx.max(sy).max(1.0);
+
+		self.context.line_width(width);
+		width
+	}
+
+	/// The pixels per logical unit.
+	fn scale(&self) -> (f64, f64) {
+		let ((x1, y1), (x2, y2)) = self.screen;
+
+		(self.size.0 / ((x2 - x1) + (x2 - x1).signum()).abs(),
+		 self.size.1 / ((y2 - y1) + (y2 - y1).signum()).abs())
+	}
+
+	/// Convert from logical units to pixels.
+	fn pixel(&self, (x, y): (f64, f64)) -> (f64, f64) {
+		let ((x1, y1), (x2, y2)) = self.screen;
+		let (sx, sy)             = self.scale();
+
+		((x - x1) * sx * (x2 - x1).signum() + 0.5,
+		 (y - y1) * sy * (y2 - y1).signum() + 0.5)
+	}
+
+	/// Resolve a coordinate relative to the current position.
+	fn resolve(&self, (x, y): Coordinate) -> (f64, f64) {
+		fn axis(value: Option<(f64, bool)>, current: f64) -> f64 {
+			match value {
+				Some((value, true))  => current + value,
+				Some((value, false)) => value,
+				None                 => current,
+			}
+		}
+
+		(axis(x, self.position.0), axis(y, self.position.1))
+	}
+
+	/// Parse a coordinate, after the opening bracket.
+	fn coordinate(&mut self) -> Coordinate {
+		let x = self.number();
+		let y = if self.peek() == Some(b',') {
+			self.offset += 1;
+			self.number()
+		}
+		else {
+			None
+		};
+
+		self.group(b']');
+		(x, y)
+	}
+
+	/// Parse a color, either as an index in the color map or a specification
+	/// within parenthesis.
+	fn color(&mut self) -> Option<Rgba<f64>> {
+		if self.peek() != Some(b'(') {
+			let (index, _) = try!(option self.number());
+			return self.colors.get(index.abs() as usize % 16).cloned();
+		}
+
+		self.offset += 1;
+
+		let mut named = None;
+		let mut hls   = (0.0, None, 0.0);
+
+		while let Some(byte) = self.next() {
+			match upper(byte) {
+				b')' => break,
+
+				b'H' => hls.0 = self.number().map(|v| v.0).unwrap_or(0.0),
+				b'L' => hls.1 = self.number().map(|v| v.0),
+				b'S' => hls.2 = self.number().map(|v| v.0).unwrap_or(0.0),
+
+				b'D' => named = Some((0, 0, 0)),
+				b'B' => named = Some((0, 0, 255)),
+				b'R' => named = Some((255, 0, 0)),
+				b'G' => named = Some((0, 255, 0)),
+				b'M' => named = Some((255, 0, 255)),
+				b'C' => named = Some((0, 255, 255)),
+				b'Y' => named = Some((255, 255, 0)),
+				b'W' => named = Some((255, 255, 255)),
+
+				_ => ()
+			}
+		}
+
+		if let (h, Some(l), s) = hls {
+			// ReGIS hues start from blue instead of red.
+			let (r, g, b, _): (u8, u8, u8, u8) = Rgba::from(Hsl::new(
+				RgbHue::from_radians(((h + 240.0) % 360.0).to_radians() as f32),
+				s as f32 / 100.0, l as f32 / 100.0)).to_pixel();
+
+			Some(Rgba::new_u8(r, g, b, 255))
+		}
+		else {
+			named.map(|(r, g, b)| Rgba::new_u8(r, g, b, 255))
+		}
+	}
+
+	/// Parse a number, a sign makes it relative.
+	fn number(&mut self) -> Option<(f64, bool)> {
+		let start = self.offset;
+		self.skip();
+
+		let mut sign     = None;
+		let mut value    = 0.0;
+		let mut digits   = 0;
+		let mut fraction = None;
+
+		match self.peek() {
+			Some(b'+') => sign = Some(1.0),
+			Some(b'-') => sign = Some(-1.0),
+			_          => ()
+		}
+
+		if sign.is_some() {
+			self.offset += 1;
+		}
+
+		while let Some(&byte) = self.input.get(self.offset) {
+			match byte {
+				b'0' ... b'9' => {
+					if let Some(ref mut scale) = fraction {
+						*scale /= 10.0;
+						value  += (byte - b'0') as f64 * *scale;
+					}
+					else {
+						value = value * 10.0 + (byte - b'0') as f64;
+					}
+
+					digits += 1;
+				}
+
+				b'.' if fraction.is_none() =>
+					fraction = Some(1.0),
+
+				_ =>
+					break
+			}
+
+			self.offset += 1;
+		}
+
+		if digits == 0 && sign.is_none() {
+			self.offset = start;
+			return None;
+		}
+
+		Some((value * sign.unwrap_or(1.0), sign.is_some()))
+	}
+
+	/// Parse a quoted string, doubled quotes are literal.
+	fn string(&mut self) -> String {
+		let     quote  = self.raw().unwrap_or(b'\'');
+		let mut string = Vec::new();
+
+		while let Some(byte) = self.raw() {
+			if byte == quote {
+				if self.input.get(self.offset) == Some(&quote) {
+					self.offset += 1;
+				}
+				else {
+					break;
+				}
+			}
+
+			string.push(byte);
+		}
+
+		String::from_utf8_lossy(&string).into_owned()
+	}
+
+	/// Skip an unsupported argument.
+	fn argument(&mut self) {
+		match self.peek() {
+			Some(b'(') => {
+				self.offset += 1;
+				self.group(b')');
+			}
+
+			Some(b'[') => {
+				self.offset += 1;
+				self.group(b']');
+			}
+
+			Some(b'\'') | Some(b'"') => {
+				self.string();
+			}
+
+			_ => {
+				self.number();
+			}
+		}
+	}
+
+	/// Skip until the end of the group, taking nesting and strings into
+	/// account.
+	fn group(&mut self, end: u8) {
+		let mut ends = vec![end];
+
+		while let Some(byte) = self.peek() {
+			match byte {
+				b'\'' | b'"' => {
+					self.string();
+				}
+
+				b'(' => {
+					self.offset += 1;
+					ends.push(b')');
+				}
+
+				b'[' => {
+					self.offset += 1;
+					ends.push(b']');
+				}
+
+				byte => {
+					self.offset += 1;
+
+					if ends.last() == Some(&byte) {
+						ends.pop();
+
+						if ends.is_empty() {
+							break;
+						}
+					}
+				}
+			}
+		}
+	}
+
+	/// Skip whitespace.
+	fn skip(&mut self) {
+		while let Some(&byte) = self.input.get(self.offset) {
+			match byte {
+				b' ' | b'\t' | b'\r' | b'\n' | b'\x0B' | b'\x0C' | b'\x08' =>
+					self.offset += 1,
+
+				_ =>
+					break
+			}
+		}
+	}
+
+	/// Peek the next byte, ignoring whitespace.
+	fn peek(&mut self) -> Option<u8> {
+		self.skip();
+		self.input.get(self.offset).cloned()
+	}
+
+	/// Get the next byte, ignoring whitespace.
+	fn next(&mut self) -> Option<u8> {
+		self.skip();
+		self.raw()
+	}
+
+	/// Get the next byte.
+	fn raw(&mut self) -> Option<u8> {
+		let byte = self.input.get(self.offset).cloned();
+
+		if byte.is_some() {
+			self.offset += 1;
+		}
+
+		byte
+	}
+}
+
+/// The offset of a pixel vector.
+fn direction(digit: u8) -> (f64, f64) {
+	match digit {
+		b'0' => (1.0, 0.0),
+		b'1' => (1.0, -1.0),
+		b'2' => (0.0, -1.0),
+		b'3' => (-1.0, -1.0),
+		b'4' => (-1.0, 0.0),
+		b'5' => (-1.0, 1.0),
+		b'6' => (0.0, 1.0),
+		_    => (1.0, 1.0),
+	}
+}
+
+fn upper(byte: u8) -> u8 {
+	if byte >= b'a' && byte <= b'z' {
+		byte - b'a' + b'A'
+	}
+	else {
+		byte
+	}
+}
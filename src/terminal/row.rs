@@ -15,16 +15,48 @@
 // You should have received a copy of the GNU General Public License
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::rc::Rc;
 use std::collections::VecDeque;
 use std::ops::{Deref, DerefMut};
 
 use terminal::Cell;
+use terminal::graphics::Placement;
+
+bitflags! {
+	/// Shell integration marks, as reported by OSC 133.
+	pub flags Mark: u8 {
+		const PROMPT = 1 << 0,
+		const INPUT  = 1 << 1,
+		const OUTPUT = 1 << 2,
+		const END    = 1 << 3,
+	}
+}
+
+/// The line size, as set by DECSWL, DECDWL and DECDHL.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Size {
+	/// Single width and single height.
+	Single,
+
+	/// Double width and single height.
+	Double,
+
+	/// Top half of a double width and double height line.
+	Top,
+
+	/// Bottom half of a double width and double height line.
+	Bottom,
+}
 
 /// A row within the view or scroll back.
 #[derive(PartialEq, Clone, Debug)]
 pub struct Row {
 	pub(super) inner:   VecDeque<Cell>,
 	pub(super) wrapped: bool,
+	pub(super) mark:    Mark,
+	pub(super) status:  Option<i32>,
+	pub(super) size:    Size,
+	pub(super) images:  Vec<Rc<Placement>>,
 }
 
 impl Row {
@@ -32,6 +64,26 @@ impl Row {
 	pub fn is_wrapped(&self) -> bool {
 		self.wrapped
 	}
+
+	/// Get the shell integration marks.
+	pub fn mark(&self) -> Mark {
+		self.mark
+	}
+
+	/// Get the exit status of the command that ended on this row.
+	pub fn status(&self) -> Option<i32> {
+		self.status
+	}
+
+	/// Get the line size.
+	pub fn size(&self) -> Size {
+		self.size
+	}
+
+	/// Get the images anchored to this row.
+	pub fn images(&self) -> &[Rc<Placement>] {
+		&self.images
+	}
 }
 
 impl Deref for Row {
@@ -0,0 +1,172 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+/// A control sequence the `control` parser doesn't understand, like the ones
+/// with private prefixes other than `?`, or with sub-parameters.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Sequence {
+	prefix:       Option<u8>,
+	arguments:    Vec<Vec<Option<u32>>>,
+	intermediate: Option<u8>,
+	terminator:   u8,
+}
+
+/// The maximum number of arguments, sub-parameters included.
+pub const ARGUMENTS: usize = 32;
+
+/// The maximum length of a sequence.
+pub const LENGTH: usize = 256;
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Parsed<'a> {
+	Done(&'a [u8], Sequence),
+	Incomplete,
+	Overflow,
+	Error,
+}
+
+impl<'a> Parsed<'a> {
+	/// Check if the parsing failed.
+	pub fn is_err(&self) -> bool {
+		if let Parsed::Error = *self {
+			true
+		}
+		else {
+			false
+		}
+	}
+}
+
+impl Sequence {
+	/// The private prefix, if any.
+	pub fn prefix(&self) -> Option<u8> {
+		self.prefix
+	}
+
+	/// The intermediate byte, if any.
+	pub fn intermediate(&self) -> Option<u8> {
+		self.intermediate
+	}
+
+	/// The final byte.
+	pub fn terminator(&self) -> u8 {
+		self.terminator
+	}
+
+	/// All the arguments, with their sub-parameters.
+	pub fn arguments(&self) -> &[Vec<Option<u32>>] {
+		&self.arguments
+	}
+
+	/// Get the argument at the given index, ignoring any sub-parameters.
+	pub fn get(&self, index: usize) -> Option<u32> {
+		self.arguments.get(index).and_then(|v| v[0])
+	}
+
+	/// Get the argument at the given index, or the default if missing.
+	pub fn arg(&self, index: usize, default: u32) -> u32 {
+		self.get(index).unwrap_or(default)
+	}
+}
+
+/// Parse a CSI sequence, sequences going over the limits are reported as
+/// overflowing instead of waiting for more input.
+pub fn parse(i: &[u8]) -> Parsed {
+	let mut rest = if i.starts_with(b"\x1B[") {
+		&i[2..]
+	}
+	else if i.first() == Some(&0x9B) {
+		&i[1..]
+	}
+	else if i.len() == 1 && i[0] == 0x1B {
+		return Parsed::Incomplete;
+	}
+	else {
+		return Parsed::Error;
+	};
+
+	let mut sequence = Sequence {
+		prefix:       None,
+		arguments:    Vec::new(),
+		intermediate: None,
+		terminator:   0,
+	};
+
+	if let Some(&byte) = rest.first() {
+		if byte >= b'<' && byte <= b'?' {
+			sequence.prefix = Some(byte);
+			rest = &rest[1..];
+		}
+	}
+
+	let mut current = vec![None::<u32>];
+
+	loop {
+		if i.len() - rest.len() > LENGTH ||
+		   sequence.arguments.iter().map(Vec::len).sum::<usize>() + current.len() > ARGUMENTS
+		{
+			return Parsed::Overflow;
+		}
+
+		let byte = if let Some(&byte) = rest.first() {
+			byte
+		}
+		else {
+			return Parsed::Incomplete;
+		};
+
+		rest = &rest[1..];
+
+		match byte {
+			// Parameters can't follow intermediates.
+			b'0' ... b';' if sequence.intermediate.is_some() =>
+				return Parsed::Error,
+
+			b'0' ... b'9' => {
+				let value = current.last_mut().unwrap();
+				*value    = Some(value.unwrap_or(0).saturating_mul(10).saturating_add((byte - b'0') as u32));
+			}
+
+			b':' => {
+				current.push(None);
+			}
+
+			b';' => {
+				sequence.arguments.push(current);
+				current = vec![None];
+			}
+
+			b' ' ... b'/' if sequence.intermediate.is_none() => {
+				sequence.intermediate = Some(byte);
+			}
+
+			b'@' ... b'~' => {
+				if current.len() > 1 || current[0].is_some() || !sequence.arguments.is_empty() {
+					sequence.arguments.push(current);
+				}
+
+				sequence.terminator = byte;
+				break;
+			}
+
+			_ =>
+				return Parsed::Error
+		}
+	}
+
+	Parsed::Done(rest, sequence)
+}
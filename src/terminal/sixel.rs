@@ -19,11 +19,31 @@ use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
 use std::f32;
+use std::cmp;
 
 use picto::color::{Rgba, Hsl, RgbHue};
 use control::DEC::SIXEL;
 use sys::cairo;
 
+/// Color registers, by their identifier.
+pub type Registers = HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>;
+
+/// The number of color registers reported to applications.
+pub const REGISTERS: u32 = 256;
+
+/// The VT340 default color map.
+pub const COLORS: [(u8, u8, u8); 16] = [
+	(0,   0,   0),   (51,  51,  204), (204, 36,  36),  (51,  204, 51),
+	(204, 51,  204), (51,  204, 204), (204, 204, 51),  (135, 135, 135),
+	(66,  66,  66),  (84,  84,  153), (153, 66,  66),  (84,  153, 84),
+	(153, 84,  153), (84,  153, 153), (153, 153, 84),  (204, 204, 204),
+];
+
+/// Create color registers holding the default color map.
+pub fn registers() -> Registers {
+	COLORS.iter().enumerate().map(|(i, &(r, g, b))| (i as u32, (r, g, b, 255))).collect()
+}
+
 #[derive(Debug)]
 pub struct Sixel {
 	raster: SIXEL::Header,
@@ -31,16 +51,20 @@ pub struct Sixel {
 
 	grid:     Vec<Vec<cairo::Image>>,
 	cell:     (u32, u32),
-	limit:    (u32, u32),
+	limit:    (u32, Option<u32>),
+	maximum:  (u32, u32),
 	position: (u32, u32),
 
-	colors:     HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>,
+	colors:     Registers,
 	color:      (u8, u8, u8, u8),
 	background: (u8, u8, u8, u8),
 }
 
 impl Sixel {
-	pub fn new(origin: (u32, u32), header: SIXEL::Header, background: &Rgba<f64>, cell: (u32, u32), limit: (u32, u32)) -> Self {
+	/// Create a new image, `limit` is the number of columns and optionally rows
+	/// it can span before being clipped, while `maximum` is the largest
+	/// background it can be filled with in pixels.
+	pub fn new(origin: (u32, u32), header: SIXEL::Header, background: &Rgba<f64>, cell: (u32, u32), limit: (u32, Option<u32>), maximum: (u32, u32), colors: Registers) -> Self {
 		Sixel {
 			raster: header,
 			origin: origin,
@@ -48,9 +72,10 @@ impl Sixel {
 			grid:     Default::default(),
 			cell:     cell,
 			limit:    limit,
+			maximum:  maximum,
 			position: (0, 0),
 
-			colors:     Default::default(),
+			colors:     colors,
 			color:      (0, 0, 0, 255),
 			background: (
 				(background.red   * 255.0) as u8,
@@ -64,16 +89,45 @@ impl Sixel {
 		self.origin
 	}
 
-	pub fn rows(&self) -> usize {
-		self.grid.len()
+	pub fn registers(&self) -> &Registers {
+		&self.colors
 	}
 
 	pub fn into_inner(self) -> Vec<Vec<cairo::Image>> {
 		self.grid
 	}
 
-	pub fn aspect(&mut self, aspect: (u32, u32)) {
-		self.raster.aspect = aspect;
+	pub fn aspect(&mut self, (numerator, denominator): (u32, u32)) {
+		// Missing values keep the aspect from the header.
+		if numerator == 0 || denominator == 0 {
+			return;
+		}
+
+		// A pixel can't be taller than a cell.
+		let aspect = (numerator as u64 + denominator as u64 / 2) / denominator as u64;
+		let aspect = cmp::min(aspect, cmp::max(1, self.cell.1) as u64) as u32;
+
+		self.raster.aspect = (cmp::max(1, aspect), 1);
+	}
+
+	/// Fill the declared raster size with the background color.
+	pub fn fill(&mut self, (width, height): (u32, u32)) {
+		if !self.raster.background {
+			return;
+		}
+
+		let width  = cmp::min(cmp::min(width, self.limit.0 * self.cell.0), self.maximum.0);
+		let height = self.limit.1.map(|rows| cmp::min(height, rows * self.cell.1)).unwrap_or(height);
+		let height = cmp::min(height, self.maximum.1);
+		let color  = self.background;
+
+		for y in 0 .. height {
+			for x in 0 .. width {
+				if let Some((tile, xo, yo)) = self.tile(x, y) {
+					tile.set(xo, yo, &color);
+				}
+			}
+		}
 	}
 
 	pub fn enable(&mut self, id: u32) {
@@ -114,44 +168,31 @@ impl Sixel {
 		}
 
 		for _ in 0 .. times {
-			// The X within the local grid.
-			let x = (self.position.0 / self.cell.0) as usize;
-
 			// Bail out early if the cell is beyond the terminal limit.
-			if x as u32 + self.limit.0 >= self.limit.1 {
+			if self.position.0 / self.cell.0 >= self.limit.0 {
 				break;
 			}
 
-			// The X within the image buffer.
-			let xo = self.position.0 % self.cell.0;
-
 			for (i, y) in (self.position.1 .. self.position.1 + (6 * self.raster.aspect.0)).enumerate() {
 				// The bit index within the sixel map.
 				let bit = (i as u32 / self.raster.aspect.0) as u8;
 
-				// The Y within the image buffer.
-				let yo = y as u32 % self.cell.1;
-
-				// The Y within the grid.
-				let y = (y / self.cell.1) as usize;
-
-				// If the grid doesn't have enough rows, extend it.
-				while y >= self.grid.len() {
-					self.grid.push(Vec::new());
-				}
-
-				// If the grid doesn't have enough columns, extend it.
-				while x >= self.grid[y].len() {
-					self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
-				}
-
-				// If the bit is enabled, set it.
-				if value.get(bit) {
-					self.grid[y][x].set(xo, yo, &self.color);
-				}
 				// If disabled bits should set the background color, do so.
+				let color = if value.get(bit) {
+					Some(self.color)
+				}
 				else if self.raster.background {
-					self.grid[y][x].set(xo, yo, &self.background);
+					Some(self.background)
+				}
+				else {
+					None
+				};
+
+				let x = self.position.0;
+				if let Some((tile, xo, yo)) = self.tile(x, y) {
+					if let Some(color) = color {
+						tile.set(xo, yo, &color);
+					}
 				}
 			}
 
@@ -159,10 +200,39 @@ impl Sixel {
 		}
 	}
 
+	/// Get the buffer containing the given pixel and the position within it,
+	/// extending the grid as needed, or `None` if the pixel is clipped.
+	fn tile(&mut self, x: u32, y: u32) -> Option<(&mut cairo::Image, u32, u32)> {
+		// The position within the image buffer.
+		let (xo, yo) = (x % self.cell.0, y % self.cell.1);
+
+		// The position within the local grid.
+		let (x, y) = (x / self.cell.0, y / self.cell.1);
+
+		if x >= self.limit.0 || self.limit.1.map(|rows| y >= rows).unwrap_or(false) {
+			return None;
+		}
+
+		let (x, y) = (x as usize, y as usize);
+
+		// If the grid doesn't have enough rows, extend it.
+		while y >= self.grid.len() {
+			self.grid.push(Vec::new());
+		}
+
+		// If the grid doesn't have enough columns, extend it.
+		while x >= self.grid[y].len() {
+			self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
+		}
+
+		Some((&mut self.grid[y][x], xo, yo))
+	}
+
 	pub fn handle(&mut self, item: &SIXEL::T) {
 		match *item {
-			SIXEL::Raster { aspect, .. } => {
+			SIXEL::Raster { aspect, size } => {
 				self.aspect(aspect);
+				self.fill(size);
 			}
 
 			SIXEL::Enable(id) => {
@@ -19,33 +19,49 @@ use std::ops::Index;
 use std::sync::Arc;
 use std::io::{self, Write};
 use std::mem;
+use std::rc::Rc;
 use std::vec;
 use std::str;
+use std::char;
 use std::cmp;
+use std::iter;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
+use std::path::{Path, PathBuf};
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 use picto::Region;
 use picto::color::Rgba;
 use control::{self, Control, C0, C1, DEC, CSI, SGR};
+use base64;
+use libc;
 use util;
 use error;
 use config::{self, Config};
 use config::style::Shape;
 use style::{self, Style};
+use platform::Clipboard;
 use platform::key::{self, Key};
 use platform::mouse::{self, Mouse};
-use terminal::{Iter, Touched, Cell, Tabs, Grid, Sixel, cell};
+use terminal::{Iter, Touched, Cell, Tabs, Grid, Palette, Link, Links, Sixel, Regis, Keyboard, Graphics, Inline, cell, sixel, graphics};
 use terminal::mode::{self, Mode};
 use terminal::cursor::{self, Cursor};
 use terminal::touched;
+use terminal::row;
+use sys::cairo;
+use terminal::charset;
+use terminal::keyboard;
 use terminal::input::{self, Input};
+use terminal::sequence::{self, Sequence};
 use interface::Action;
 
 #[derive(Debug)]
 pub struct Terminal {
-	config: Arc<Config>,
-	font:   (u32, u32),
+	config:  Arc<Config>,
+	font:    (u32, u32),
+	palette: Palette,
 
 	region:  Region,
 	cache:   Option<Vec<u8>>,
@@ -53,22 +69,52 @@ pub struct Terminal {
 	mode:    Mode,
 	click:   Option<mouse::Click>,
 	command: Option<Command>,
-
-	scroll: Option<u32>,
-	grid:   Grid,
-	tabs:   Tabs,
-
-	cursor: Cursor,
-	saved:  Option<Cursor>,
+	request: Option<(char, &'static str)>,
+	cwd:     Option<PathBuf>,
+
+	title:  String,
+	icon:   String,
+	titles: Vec<(String, String)>,
+
+	scroll:   Option<u32>,
+	grid:     Grid,
+	inactive: Grid,
+	tabs:     Tabs,
+
+	cursor:   Cursor,
+	saved:    Option<Cursor>,
+	grapheme: Option<(u32, u32)>,
+	modes:    HashMap<u32, bool, BuildHasherDefault<FnvHasher>>,
+
+	// Each screen has its own keyboard enhancement flags, the inactive ones
+	// are swapped in with the screen.
+	keyboard: Vec<Keyboard>,
+	stacked:  Vec<Keyboard>,
+	modify:   u8,
+
+	registers: sixel::Registers,
+	graphics:  Graphics,
+
+	links: Links,
+	link:  Option<Rc<Link>>,
+	hover: Option<Rc<Link>>,
 }
 
 unsafe impl Send for Terminal { }
 
+/// The maximum width and height of the window in pixels.
+const WINDOW: u32 = 32767;
+
+/// The maximum length of the body of a control string, the rest is dropped.
+pub const STRING: usize = 32 * 1024 * 1024;
+
 #[derive(Debug)]
 enum Command {
 	Device,
 	Sixel(Sixel),
-	Internal,
+	Regis(Regis),
+	Application(Vec<u8>),
+	Internal(Vec<u8>),
 }
 
 macro_rules! term {
@@ -76,10 +122,23 @@ macro_rules! term {
 		$term.cursor.charsets[$term.cursor.charset as usize]
 	);
 
+	($term:ident; margins) => (
+		if $term.cursor.margin != (0, $term.region.width - 1) {
+			Some($term.cursor.margin)
+		}
+		else {
+			None
+		}
+	);
+
+	($term:ident; inside margins) => (
+		$term.cursor.x() >= $term.cursor.margin.0 && $term.cursor.x() <= $term.cursor.margin.1
+	);
+
 	($term:ident; scroll! up $n:tt) => (
-		if $term.cursor.scroll == (0, $term.region.height - 1) {
+		if $term.cursor.scroll == (0, $term.region.height - 1) && term!($term; margins).is_none() {
 			$term.touched.all();
-			$term.grid.up($n, None);
+			$term.grid.up($n, None, None);
 		}
 		else {
 			term!($term; scroll up $n)
@@ -92,7 +151,7 @@ macro_rules! term {
 
 	($term:ident; scroll up $n:tt from $y:expr) => ({
 		if $y < $term.cursor.scroll.1 {
-			$term.grid.up($n as u32, Some(($y, $term.cursor.scroll.1)));
+			$term.grid.up($n as u32, Some(($y, $term.cursor.scroll.1)), term!($term; margins));
 
 			for y in $y ... $term.cursor.scroll.1 {
 				$term.touched.line(y);
@@ -106,7 +165,7 @@ macro_rules! term {
 
 	($term:ident; scroll down $n:tt from $y:expr) => ({
 		if $y < $term.cursor.scroll.1 {
-			$term.grid.down($n as u32, Some(($y, $term.cursor.scroll.1)));
+			$term.grid.down($n as u32, Some(($y, $term.cursor.scroll.1)), term!($term; margins));
 
 			for y in $y ... $term.cursor.scroll.1 {
 				$term.touched.line(y);
@@ -129,10 +188,27 @@ macro_rules! term {
 	($term:ident; cursor $($travel:tt)*) => ({
 		$term.touched.push($term.cursor.position());
 		let r = $term.cursor.travel(cursor::$($travel)*);
+
+		// Double width lines only have half the columns.
+		let limit = term!($term; columns $term.cursor.y());
+		if $term.cursor.x() >= limit {
+			let x = $term.cursor.x();
+			$term.cursor.travel(cursor::Left(x - (limit - 1)));
+		}
+
 		$term.touched.push($term.cursor.position());
 		r
 	});
 
+	($term:ident; columns $y:expr) => (
+		if $term.grid.view()[$y as usize].size() == row::Size::Single {
+			$term.region.width
+		}
+		else {
+			$term.region.width / 2
+		}
+	);
+
 	($term:ident; tab $n:expr) => ({
 		let (x, _) = term!($term; cursor);
 		term!($term; cursor Position(Some($term.tabs.next($n, x)), None));
@@ -150,11 +226,13 @@ impl Terminal {
 	pub fn new(config: Arc<Config>, font: (u32, u32), (width, height): (u32, u32)) -> error::Result<Self> {
 		let region = Region::from(0, 0, width, height);
 		let grid   = Grid::new(width, height, config.environment().scroll());
+		let alt    = Grid::new(width, height, 0);
 		let tabs   = Tabs::new(width, height);
 
 		Ok(Terminal {
-			config: config.clone(),
-			font:   font,
+			config:  config.clone(),
+			font:    font,
+			palette: Palette::new(config.clone()),
 
 			region:  region,
 			cache:   Default::default(),
@@ -162,13 +240,33 @@ impl Terminal {
 			mode:    Mode::default(),
 			click:   None,
 			command: None,
+			request: None,
+			cwd:     None,
+
+			title:  String::new(),
+			icon:   String::new(),
+			titles: Vec::new(),
 
-			scroll: None,
-			grid:   grid,
-			tabs:   tabs,
+			scroll:   None,
+			grid:     grid,
+			inactive: alt,
+			tabs:     tabs,
 
-			cursor: Cursor::new(config.clone(), width, height),
-			saved:  None,
+			cursor:   Cursor::new(config.clone(), width, height),
+			saved:    None,
+			grapheme: None,
+			modes:    Default::default(),
+
+			keyboard: Vec::new(),
+			stacked:  Vec::new(),
+			modify:   0,
+
+			registers: sixel::registers(),
+			graphics:  Graphics::default(),
+
+			links: Links::default(),
+			link:  None,
+			hover: None,
 		})
 	}
 
@@ -177,6 +275,11 @@ impl Terminal {
 		&self.config
 	}
 
+	/// Get the color palette.
+	pub fn palette(&self) -> &Palette {
+		&self.palette
+	}
+
 	/// Get the number of columns.
 	pub fn columns(&self) -> u32 {
 		self.region.width
@@ -192,17 +295,43 @@ impl Terminal {
 		self.mode
 	}
 
-	/// Get the internal grid.
+	/// Get the grid of the active screen.
 	pub fn grid(&self) -> &Grid {
 		&self.grid
 	}
 
+	/// Get the line size of the given row.
+	pub fn size(&self, y: u32) -> row::Size {
+		self.grid.view()[y as usize].size()
+	}
+
+	/// Check if the alternate screen is active.
+	pub fn alternate(&self) -> bool {
+		self.mode.contains(mode::ALTERNATE)
+	}
+
 	/// Get the cursor.
 	pub fn cursor(&self) -> cursor::Cell {
 		let (x, y) = term!(self; cursor);
 		cursor::Cell::new(&self.cursor, cell::Position::new(x, y, &self.grid[(x, y)]))
 	}
 
+	/// Get the current working directory, as reported by the shell.
+	pub fn cwd(&self) -> Option<&Path> {
+		self.cwd.as_ref().map(AsRef::as_ref)
+	}
+
+	/// Get the link under the mouse.
+	pub fn hover(&self) -> Option<&Rc<Link>> {
+		self.hover.as_ref()
+	}
+
+	/// Get the images reaching into the view, with the row they're anchored
+	/// to.
+	pub fn images(&self) -> Vec<(i32, Rc<graphics::Placement>)> {
+		self.grid.images()
+	}
+
 	/// Get the region of the terminal.
 	pub fn region(&self) -> Region {
 		self.region
@@ -217,9 +346,13 @@ impl Terminal {
 	pub fn resize(&mut self, width: u32, height: u32) {
 		self.region.width  = width;
 		self.region.height = height;
+		self.grapheme      = None;
 
 		self.tabs.resize(width, height);
 
+		// The inactive screen doesn't own the cursor, so its offset is ignored.
+		self.inactive.resize(width, height);
+
 		match self.grid.resize(width, height) {
 			n if n > 0 => {
 				self.cursor.travel(cursor::Down(n as u32));
@@ -233,7 +366,17 @@ impl Terminal {
 		}
 
 		self.cursor.resize(width, height);
-		self.saved = None;
+
+		// The saved cursor is restored when leaving the alternate screen, so keep
+		// it around.
+		if self.alternate() {
+			if let Some(saved) = self.saved.as_mut() {
+				saved.resize(width, height);
+			}
+		}
+		else {
+			self.saved = None;
+		}
 	}
 
 	/// Enable or disable blinking and return the affected cells.
@@ -275,6 +418,16 @@ impl Terminal {
 
 	/// Paste something to the terminal.
 	pub fn paste<O: Write>(&mut self, value: &[u8], mut output: O) -> io::Result<()> {
+		// Pastes come back in the order they were requested, so the first one
+		// after a clipboard query is its answer, empty if the clipboard was.
+		if let Some((selection, end)) = self.request.take() {
+			return write!(output, "\x1B]52;{};{}{}", selection, base64::encode(value), end);
+		}
+
+		if value.is_empty() {
+			return Ok(());
+		}
+
 		if self.mode.contains(mode::BRACKETED_PASTE) {
 			try!(output.write_all(b"\x1B[200~"));
 		}
@@ -288,8 +441,69 @@ impl Terminal {
 		Ok(())
 	}
 
-	/// Handle a key.
-	pub fn key<O: Write>(&mut self, key: Key, mut output: O) -> io::Result<()> {
+	/// Handle a key, echoing it locally if send/receive mode requests it.
+	pub fn key<O: Write>(&mut self, key: Key, mut output: O) -> error::Result<(vec::IntoIter<Action>, touched::Iter)> {
+		if !self.mode.contains(mode::ECHO) {
+			try!(self.encode(key, output.by_ref()));
+			return Ok((Vec::new().into_iter(), touched::Iter::empty()));
+		}
+
+		let mut buffer = Vec::new();
+		try!(self.encode(key, &mut buffer));
+		try!(output.write_all(&buffer));
+
+		Ok((Vec::new().into_iter(), self.echo(&buffer)))
+	}
+
+	/// Echo an encoded key locally, only text and line movements are shown and
+	/// whatever the application is in the middle of sending is left alone.
+	fn echo(&mut self, input: &[u8]) -> touched::Iter {
+		let grapheme = self.grapheme.take();
+		let shift    = self.cursor.shift.take();
+
+		// Escape sequences are for the application to interpret.
+		if let Ok(string) = str::from_utf8(input) {
+			if !string.contains('\x1B') {
+				for ch in string.graphemes(true) {
+					match ch {
+						"\r" => {
+							term!(self; cursor Left(self.cursor.x()));
+						}
+
+						"\n" | "\r\n" => {
+							if ch == "\r\n" {
+								term!(self; cursor Left(self.cursor.x()));
+							}
+
+							if term!(self; cursor Down(1)).is_some() {
+								term!(self; scroll! up 1);
+							}
+						}
+
+						"\x08" | "\x7F" => {
+							term!(self; cursor Left(1));
+						}
+
+						ch if ch.chars().any(char::is_control) =>
+							(),
+
+						ch => {
+							self.grapheme = None;
+							self.insert(ch);
+						}
+					}
+				}
+			}
+		}
+
+		self.grapheme     = grapheme;
+		self.cursor.shift = shift;
+
+		self.touched.iter(self.region)
+	}
+
+	/// Encode a key for the application.
+	fn encode<O: Write>(&self, key: Key, mut output: O) -> io::Result<()> {
 		use platform::key::{Value, Button, Keypad};
 
 		macro_rules! write {
@@ -330,6 +544,21 @@ impl Terminal {
 
 		debug!(target: "cancer::terminal::key", "key {:?}", key);
 
+		if let Some(&flags) = self.keyboard.last() {
+			if try!(keyboard::progressive(&key, flags, output.by_ref())) {
+				return Ok(());
+			}
+		}
+
+		// Releases are only reported by the progressive keyboard protocol.
+		if key.state() == key::State::Release {
+			return Ok(());
+		}
+
+		if self.modify == 2 && try!(keyboard::modified(&key, output.by_ref())) {
+			return Ok(());
+		}
+
 		match *key.value() {
 			Value::Char(ref string) => {
 				if key.modifier().contains(key::ALT) {
@@ -672,9 +901,50 @@ impl Terminal {
 	}
 
 	/// Handle mouse inputs.
-	pub fn mouse<O: Write>(&mut self, mouse: Mouse, mut output: O) -> io::Result<()> {
+	pub fn mouse<O: Write>(&mut self, mouse: Mouse, mut output: O) -> io::Result<touched::Iter> {
 		debug!(target: "cancer::terminal::mouse", "mouse {:?}", mouse);
 
+		if let Mouse::Motion(motion) = mouse {
+			self.hovered(motion.position.x, motion.position.y);
+		}
+
+		try!(self.report(mouse, output.by_ref()));
+		Ok(self.touched.iter(self.region))
+	}
+
+	/// Track the link under the mouse, touching the cells of the links that
+	/// changed.
+	fn hovered(&mut self, x: u32, y: u32) {
+		let link = {
+			let (x, y) = if let Cell::Reference(offset) = self.grid[(x, y)] {
+				(x - offset as u32, y)
+			}
+			else {
+				(x, y)
+			};
+
+			self.grid[(x, y)].link().cloned()
+		};
+
+		if Link::same(link.as_ref(), self.hover.as_ref()) {
+			return;
+		}
+
+		for (x, y) in self.region.absolute() {
+			let cell = &self.grid[(x, y)];
+
+			if cell.link().is_some() &&
+			   (Link::same(cell.link(), link.as_ref()) || Link::same(cell.link(), self.hover.as_ref()))
+			{
+				self.touched.mark(x, y);
+			}
+		}
+
+		self.hover = link;
+	}
+
+	/// Report mouse events.
+	fn report<O: Write>(&mut self, mouse: Mouse, mut output: O) -> io::Result<()> {
 		// If none of the mouse reporting modes are set, bail out.
 		if !self.mode.intersects(mode::MOUSE) {
 			return Ok(());
@@ -689,11 +959,14 @@ impl Terminal {
 				// If no button is being clicked, motions aren't reported.
 				if let Some(mut click) = self.click {
 					// Don't report the same position twice.
-					if click.position == motion.position {
+					if click.position == motion.position &&
+					   (!self.mode.contains(mode::MOUSE_PIXELS) || click.pixel == motion.pixel)
+					{
 						return Ok(());
 					}
 
 					click.position = motion.position;
+					click.pixel    = motion.pixel;
 					click
 				}
 				else if self.mode.contains(mode::MOUSE_MANY) {
@@ -702,6 +975,7 @@ impl Terminal {
 						modifier: motion.modifier,
 						button:   mouse::Button::Middle,
 						position: motion.position,
+						pixel:    motion.pixel,
 					}
 				}
 				else {
@@ -718,16 +992,19 @@ impl Terminal {
 			self.click = Some(click);
 		}
 
-		let mut button = if !self.mode.contains(mode::MOUSE_SGR) && !click.press {
+		let mut button = if !self.mode.intersects(mode::MOUSE_SGR | mode::MOUSE_PIXELS) && !click.press {
 			3
 		}
 		else {
 			match click.button {
-				mouse::Button::Left   => 0,
-				mouse::Button::Middle => 1,
-				mouse::Button::Right  => 2,
-				mouse::Button::Up     => 64,
-				mouse::Button::Down   => 65,
+				mouse::Button::Left     => 0,
+				mouse::Button::Middle   => 1,
+				mouse::Button::Right    => 2,
+				mouse::Button::Up       => 64,
+				mouse::Button::Down     => 65,
+				mouse::Button::Back     => 128,
+				mouse::Button::Forward  => 129,
+				mouse::Button::Other(n) => 128 + n.saturating_sub(8),
 			}
 		};
 
@@ -752,6 +1029,31 @@ impl Terminal {
 				x      = click.position.x + 1,
 				y      = click.position.y + 1));
 		}
+		else if self.mode.contains(mode::MOUSE_PIXELS) {
+			try!(write!(output, "\x1B[<{button};{x};{y}{mode}",
+				mode   = if click.press { 'M' } else { 'm' },
+				button = button,
+				x      = click.pixel.x + 1,
+				y      = click.pixel.y + 1));
+		}
+		else if self.mode.contains(mode::MOUSE_URXVT) {
+			try!(write!(output, "\x1B[{button};{x};{y}M",
+				button = 32 + button as u32,
+				x      = click.position.x + 1,
+				y      = click.position.y + 1));
+		}
+		else if self.mode.contains(mode::MOUSE_UTF8) {
+			// Values are encoded as UTF-8 characters, and coordinates cannot go
+			// beyond what fits in two bytes.
+			if click.position.x < 2015 && click.position.y < 2015 {
+				let encode = |value: u32| char::from_u32(32 + value).unwrap();
+
+				try!(write!(output, "\x1B[M{}{}{}",
+					encode(button as u32),
+					encode(click.position.x + 1),
+					encode(click.position.y + 1)));
+			}
+		}
 		else if click.position.x < 223 && click.position.y < 223 {
 			try!(output.write_all(b"\x1B[M"));
 			try!(output.write_all(&[
@@ -781,6 +1083,8 @@ impl Terminal {
 
 		while !input.is_empty() {
 			if let Some(command) = self.command.take() {
+				self.grapheme = None;
+
 				if !C1::is_string(input[0]) && C1::is_end(input).is_err() {
 					debug!(target: "cancer::terminal::input", "invalid string {:0x}", input[0]);
 					break;
@@ -792,11 +1096,28 @@ impl Terminal {
 							control::Result::Done(rest, header) => {
 								input = rest;
 
-								let origin = term!(self; cursor);
-								let sixel  = Sixel::new(origin, header,
-									self.cursor.style().background().unwrap_or(self.config.style().color().background()),
-									(self.font.0, self.font.1),
-									(origin.0, self.region.width));
+								// In display mode the image starts at the top left corner
+								// and is clipped at the bottom instead of scrolling.
+								let display = self.mode.contains(mode::SIXEL_DISPLAY);
+								let origin  = if display { (0, 0) } else { term!(self; cursor) };
+								let limit   = (self.region.width - origin.0,
+									if display { Some(self.region.height) } else { None });
+
+								// Private registers start from the default color map.
+								let registers = if self.mode.contains(mode::SIXEL_PRIVATE) {
+									sixel::registers()
+								}
+								else {
+									self.registers.clone()
+								};
+
+								// The background is filled at most up to the geometry reported by
+								// XTSMGRAPHICS.
+								let maximum = (self.region.width * self.font.0, self.region.height * self.font.1);
+
+								let sixel = Sixel::new(origin, header,
+									self.cursor.style().background().unwrap_or(self.palette.background()),
+									(self.font.0, self.font.1), limit, maximum, registers);
 
 								self.command = Some(Command::Sixel(sixel));
 								continue;
@@ -812,40 +1133,85 @@ impl Terminal {
 							control::Result::Error(..) => ()
 						}
 
-						// TODO: insert ReGIS header check
+						// Request a status string.
+						if input.starts_with(b"$q") {
+							match C1::string(&input[2..]) {
+								control::Result::Done(rest, request) => {
+									input = rest;
+									try!(self.status(request, output.by_ref()));
+									continue;
+								}
+
+								control::Result::Incomplete(..) => {
+									debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+									self.cache   = Some(input.to_vec());
+									self.command = Some(Command::Device);
+									break;
+								}
+
+								control::Result::Error(..) => ()
+							}
+						}
+						else if b"$q".starts_with(input) {
+							debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+							self.cache   = Some(input.to_vec());
+							self.command = Some(Command::Device);
+							break;
+						}
+
+						// ReGIS graphics, with an optional mode before the final `p`.
+						let digits = input.iter().take_while(|&&b| b >= b'0' && b <= b'9').count();
+
+						if digits < input.len() && input[digits] == b'p' {
+							input = &input[digits + 1 ..];
+
+							let regis = Regis::new(
+								self.cursor.style().foreground().unwrap_or(self.palette.foreground()));
+
+							self.command = Some(Command::Regis(regis));
+							continue;
+						}
+						else if digits == input.len() {
+							debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+							self.cache   = Some(input.to_vec());
+							self.command = Some(Command::Device);
+							break;
+						}
 					}
 
-					Command::Sixel(mut sixel) => {
+					Command::Regis(mut regis) => {
 						match C1::is_end(input) {
-							// Move the drawn grid into the terminal.
+							// Draw the collected commands.
 							control::Result::Done(rest, _) => {
 								input = rest;
+								self.regis(regis);
 
-								let rows = sixel.rows();
-								let edge = sixel.origin().0;
+								continue;
+							}
 
-								for (i, row) in sixel.into_inner().into_iter().enumerate() {
-									for buffer in row {
-										let (x, y) = term!(self; cursor);
-										self.grid[(x, y)].make_image(buffer, self.cursor.style().clone());
-										term!(self; cursor Right(1));
-									}
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Regis(regis));
+								break;
+							}
 
-									// Clean leftover references.
-									let (x, y) = term!(self; cursor);
-									self.grid.clean_references(x, y);
+							control::Result::Error(..) => ()
+						}
 
-									// If it's the last row, skip cursor movement.
-									if i == rows - 1 {
-										continue;
-									}
+						let length = cmp::max(1, input.iter().take_while(|&&b| C1::is_string(b)).count());
+						regis.feed(&input[.. length]);
 
-									if term!(self; cursor Down(1)).is_some() {
-										term!(self; scroll! up 1);
-									}
+						input        = &input[length ..];
+						self.command = Some(Command::Regis(regis));
+					}
 
-									term!(self; cursor Position(Some(edge), None));
-								}
+					Command::Sixel(mut sixel) => {
+						match C1::is_end(input) {
+							// Move the drawn grid into the terminal.
+							control::Result::Done(rest, _) => {
+								input = rest;
+								self.sixel(sixel);
 
 								continue;
 							}
@@ -882,31 +1248,145 @@ impl Terminal {
 						}
 					}
 
-					Command::Internal => {
-						match C1::string(input) {
-							control::Result::Done(rest, item) => {
+					Command::Application(mut body) => {
+						match C1::is_end(input) {
+							control::Result::Done(rest, _) => {
+								input = rest;
+
+								if body.starts_with(b"G") {
+									if let Ok(string) = str::from_utf8(&body[1..]) {
+										try!(self.graphics(string, output.by_ref()));
+									}
+								}
+
+								continue;
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Application(body));
+								break;
+							}
+
+							control::Result::Error(..) => ()
+						}
+
+						let length = input.iter().take_while(|&&b| C1::is_string(b)).count();
+						body.extend_from_slice(&input[.. cmp::min(length, STRING.saturating_sub(body.len()))]);
+
+						input        = &input[length ..];
+						self.command = Some(Command::Application(body));
+					}
+
+					Command::Internal(mut body) => {
+						match C1::is_end(input) {
+							control::Result::Done(rest, end) => {
 								input = rest;
-								actions.extend(self.command(item));
+
+								// Replies end the same way the query did.
+								let end = if end == b"\x07" { "\x07" } else { "\x1B\\" };
+
+								if let Ok(string) = str::from_utf8(&body) {
+									actions.extend(self.command(string, end, output.by_ref())?);
+								}
+
+								continue;
 							}
 
 							control::Result::Incomplete(..) => {
 								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
 								self.cache   = Some(input.to_vec());
-								self.command = Some(Command::Internal);
+								self.command = Some(Command::Internal(body));
 								break;
 							}
 
-							control::Result::Error(..) =>
-								break,
+							control::Result::Error(..) => ()
 						}
+
+						let length = input.iter().take_while(|&&b| C1::is_string(b)).count();
+						body.extend_from_slice(&input[.. cmp::min(length, STRING.saturating_sub(body.len()))]);
+
+						input        = &input[length ..];
+						self.command = Some(Command::Internal(body));
 					}
 				}
 
 				continue;
 			}
 
+			// The control parser reports the UK set as Latin-1, since they share the
+			// final byte, but only the 94 character designators select it.
+			if input.len() >= 3 && input[0] == 0x1B && b"()*+".contains(&input[1]) && input[2] == b'A' {
+				self.cursor.charsets[(input[1] - b'(') as usize] = DEC::charset::NRCS::UK.into();
+
+				input = &input[3..];
+				continue;
+			}
+
+			// Locking shifts aren't supported by the control parser.
+			if input.len() >= 2 && input[0] == 0x1B && b"no~}|".contains(&input[1]) {
+				match input[1] {
+					b'n' => self.cursor.charset = 2,
+					b'o' => self.cursor.charset = 3,
+					b'~' => self.cursor.right   = Some(1),
+					b'}' => self.cursor.right   = Some(2),
+					_    => self.cursor.right   = Some(3),
+				}
+
+				self.grapheme = None;
+				input         = &input[2..];
+				continue;
+			}
+
+			// Control sequences are parsed here first, since the control parser
+			// mistakes the ones it doesn't know for a lone escape.
+			let mut skip = None;
+
+			if input.starts_with(b"\x1B[") || input.first() == Some(&0x9B) {
+				match sequence::parse(input) {
+					sequence::Parsed::Done(rest, sequence) => {
+						if self.extended(&sequence) {
+							debug!(target: "cancer::terminal::input::parsed", "sequence: {:?}", sequence);
+
+							input = rest;
+							actions.extend(self.sequence(sequence, output.by_ref())?);
+							continue;
+						}
+
+						skip = Some((rest, sequence));
+					}
+
+					sequence::Parsed::Incomplete => {
+						debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+						self.cache = Some(input.to_vec());
+						break;
+					}
+
+					// Drop the introducer, the rest is shown as text.
+					sequence::Parsed::Overflow => {
+						debug!(target: "cancer::terminal::input", "overflowing sequence: {:?}", input);
+						input = &input[if input[0] == 0x9B { 1 } else { 2 } ..];
+						continue;
+					}
+
+					sequence::Parsed::Error => ()
+				}
+			}
+
 			// Try to parse the input.
 			let item = match control::parse(input) {
+				// A complete sequence the control parser doesn't know.
+				control::Result::Done(_, Control::C0(C0::Escape)) |
+				control::Result::Incomplete(_) |
+				control::Result::Error(_) if skip.is_some() => {
+					let (rest, sequence) = skip.take().unwrap();
+					debug!(target: "cancer::terminal::unhandled", "unhandled sequence: {:?}", sequence);
+
+					input = rest;
+					continue;
+				}
+
 				// No control code.
 				control::Result::Error(_) => {
 					let kind = match input::parse(input) {
@@ -979,10 +1459,28 @@ impl Terminal {
 	fn control<O: Write>(&mut self, control: Control, mut output: O) -> error::Result<Vec<Action>> {
 		let mut actions = Vec::new();
 
+		// Graphemes only continue through text.
+		self.grapheme = None;
+
 		match control {
 			// Attributes.
 			Control::C1(C1::ControlSequence(CSI::DeviceAttributes(0))) => {
-				try!(output.write_all(b"\033[?64;6;21c"));
+				let device = self.config.environment().device();
+
+				try!(write!(output, "\x1B[?{}", match device.level() {
+					1 => 1,
+					n => 60 + n as u32,
+				}));
+
+				for feature in device.features() {
+					try!(write!(output, ";{}", feature));
+				}
+
+				try!(output.write_all(b"c"));
+			}
+
+			Control::C0(C0::Enquiry) => {
+				try!(output.write_all(self.config.environment().device().answerback().as_bytes()));
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeviceStatusReport(CSI::Report::CursorPosition))) => {
@@ -1025,8 +1523,9 @@ impl Terminal {
 						CSI::Mode::InsertionReplacement =>
 							self.mode.insert(mode::INSERT),
 
+						// Local echo is enabled when send/receive mode is reset.
 						CSI::Mode::SendReceive =>
-							self.mode.insert(mode::ECHO),
+							self.mode.remove(mode::ECHO),
 
 						CSI::Mode::LineFeed =>
 							self.mode.insert(mode::CRLF),
@@ -1066,6 +1565,9 @@ impl Terminal {
 						DEC::Mode::SmallFont =>
 							actions.push(Action::Resize(132, 24)),
 
+						DEC::Mode::SetMargins =>
+							self.mode.insert(mode::MARGINS),
+
 						mode =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
 					}
@@ -1083,6 +1585,15 @@ impl Terminal {
 						2004 =>
 							self.mode.insert(mode::BRACKETED_PASTE),
 
+						2026 =>
+							self.mode.insert(mode::SYNCHRONIZED),
+
+						80 =>
+							self.mode.insert(mode::SIXEL_DISPLAY),
+
+						1070 =>
+							self.mode.insert(mode::SIXEL_PRIVATE),
+
 						9 | 1000 | 1002 | 1003 => {
 							self.mode.remove(mode::MOUSE);
 							self.mode.insert(match arg {
@@ -1094,8 +1605,27 @@ impl Terminal {
 							});
 						}
 
-						1006 =>
-							self.mode.insert(mode::MOUSE_SGR),
+						1005 | 1006 | 1015 | 1016 => {
+							self.mode.remove(mode::MOUSE_FORMAT);
+							self.mode.insert(match arg {
+								1005 => mode::MOUSE_UTF8,
+								1006 => mode::MOUSE_SGR,
+								1015 => mode::MOUSE_URXVT,
+								1016 => mode::MOUSE_PIXELS,
+								_    => unreachable!()
+							});
+						}
+
+						47 | 1047 =>
+							self.screen(true),
+
+						1049 => {
+							if !self.alternate() {
+								self.saved = Some(self.cursor.clone());
+								self.screen(true);
+								self.clear();
+							}
+						}
 
 						n =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled set: {}", n)
@@ -1115,7 +1645,7 @@ impl Terminal {
 							self.mode.remove(mode::INSERT),
 
 						CSI::Mode::SendReceive =>
-							self.mode.remove(mode::ECHO),
+							self.mode.insert(mode::ECHO),
 
 						CSI::Mode::LineFeed =>
 							self.mode.remove(mode::CRLF),
@@ -1155,6 +1685,11 @@ impl Terminal {
 						DEC::Mode::SmallFont =>
 							actions.push(Action::Resize(80, 24)),
 
+						DEC::Mode::SetMargins => {
+							self.mode.remove(mode::MARGINS);
+							self.cursor.margin = (0, self.region.width - 1);
+						}
+
 						mode =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", mode)
 					}
@@ -1172,18 +1707,241 @@ impl Terminal {
 						2004 =>
 							self.mode.remove(mode::BRACKETED_PASTE),
 
+						2026 =>
+							self.mode.remove(mode::SYNCHRONIZED),
+
+						80 =>
+							self.mode.remove(mode::SIXEL_DISPLAY),
+
+						1070 =>
+							self.mode.remove(mode::SIXEL_PRIVATE),
+
 						9 | 1000 | 1002 | 1003 =>
 							self.mode.remove(mode::MOUSE),
 
+						1005 =>
+							self.mode.remove(mode::MOUSE_UTF8),
+
 						1006 =>
 							self.mode.remove(mode::MOUSE_SGR),
 
+						1015 =>
+							self.mode.remove(mode::MOUSE_URXVT),
+
+						1016 =>
+							self.mode.remove(mode::MOUSE_PIXELS),
+
+						47 =>
+							self.screen(false),
+
+						1047 => {
+							if self.alternate() {
+								self.clear();
+								self.screen(false);
+							}
+						}
+
+						1049 => {
+							if self.alternate() {
+								self.screen(false);
+
+								if let Some(saved) = self.saved.clone() {
+									self.cursor = saved;
+								}
+
+								self.touched.all();
+							}
+						}
+
 						n =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", n)
 					}
 				}
 			}
 
+			// Window manipulation and reports.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b't', None, args))) => {
+				let arg = |i: usize, default: u32| args.get(i).and_then(|v| *v).unwrap_or(default);
+
+				let (width, height) = (self.region.width, self.region.height);
+				let margin          = self.config.style().margin();
+
+				match arg(0, 0) {
+					1 =>
+						actions.push(Action::Iconify(false)),
+
+					2 =>
+						actions.push(Action::Iconify(true)),
+
+					// A missing or zero dimension is kept, and the size is bounded by the
+					// biggest window possible.
+					4 => {
+						let rows = match arg(1, 0) {
+							0 => height,
+							n => cmp::max(1, cmp::min(n, WINDOW) / self.font.1),
+						};
+
+						let columns = match arg(2, 0) {
+							0 => width,
+							n => cmp::max(1, cmp::min(n, WINDOW) / self.font.0),
+						};
+
+						actions.push(Action::Resize(columns, rows));
+					}
+
+					5 =>
+						actions.push(Action::Raise),
+
+					8 => {
+						let rows = match arg(1, 0) {
+							0 => height,
+							n => cmp::min(n, WINDOW / self.font.1),
+						};
+
+						let columns = match arg(2, 0) {
+							0 => width,
+							n => cmp::min(n, WINDOW / self.font.0),
+						};
+
+						actions.push(Action::Resize(cmp::max(1, columns), cmp::max(1, rows)));
+					}
+
+					11 =>
+						try!(output.write_all(b"\x1B[1t")),
+
+					14 if arg(1, 0) == 2 =>
+						try!(write!(output, "\x1B[4;{};{}t",
+							height * self.font.1 + margin * 2, width * self.font.0 + margin * 2)),
+
+					14 =>
+						try!(write!(output, "\x1B[4;{};{}t", height * self.font.1, width * self.font.0)),
+
+					15 =>
+						try!(write!(output, "\x1B[5;{};{}t",
+							height * self.font.1 + margin * 2, width * self.font.0 + margin * 2)),
+
+					16 =>
+						try!(write!(output, "\x1B[6;{};{}t", self.font.1, self.font.0)),
+
+					18 =>
+						try!(write!(output, "\x1B[8;{};{}t", height, width)),
+
+					19 =>
+						try!(write!(output, "\x1B[9;{};{}t", height, width)),
+
+					// Titles can be set by anything that gets printed, so they're only
+					// reported back when allowed.
+					20 =>
+						try!(write!(output, "\x1B]L{}\x1B\\",
+							if self.config.environment().report().title() { &self.icon[..] } else { "" })),
+
+					21 =>
+						try!(write!(output, "\x1B]l{}\x1B\\",
+							if self.config.environment().report().title() { &self.title[..] } else { "" })),
+
+					// Push the title and/or icon label on the stack.
+					22 => {
+						if self.titles.len() >= 10 {
+							self.titles.remove(0);
+						}
+
+						self.titles.push((self.title.clone(), self.icon.clone()));
+					}
+
+					// Pop the title and/or icon label from the stack.
+					23 => {
+						if let Some((title, icon)) = self.titles.pop() {
+							let which = arg(1, 0);
+
+							if which == 0 || which == 1 {
+								self.icon = icon;
+							}
+
+							if which == 0 || which == 2 {
+								self.title = title.clone();
+								actions.push(Action::Title(title));
+							}
+						}
+					}
+
+					n =>
+						debug!(target: "cancer::terminal::unhandled", "unhandled window operation: {}", n)
+				}
+			}
+
+			// Request ANSI or private mode.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'p', Some(b'$'), ref args))) |
+			Control::C1(C1::ControlSequence(CSI::Private(b'p', Some(b'$'), ref args))) => {
+				let private = if let Control::C1(C1::ControlSequence(CSI::Private(..))) = control { true } else { false };
+
+				if let Some(mode) = args.get(0).and_then(|v| *v) {
+					try!(write!(output, "\x1B[{}{};{}$y", if private { "?" } else { "" }, mode,
+						match self.query(private, mode) {
+							Some(true)  => 1,
+							Some(false) => 2,
+							None        => 4,
+						}));
+				}
+			}
+
+			// Query the keyboard enhancement flags.
+			Control::C1(C1::ControlSequence(CSI::Private(b'u', None, _))) => {
+				try!(write!(output, "\x1B[?{}u", self.keyboard.last().map(|f| f.bits()).unwrap_or(0)));
+			}
+
+			// Query graphics attributes, the color registers and the geometry can't
+			// be changed.
+			Control::C1(C1::ControlSequence(CSI::Private(b'S', None, args))) => {
+				let arg = |i: usize| args.get(i).and_then(|v| *v).unwrap_or(0);
+
+				let (width, height) = (self.region.width * self.font.0, self.region.height * self.font.1);
+
+				match (arg(0), arg(1)) {
+					(1, 1) | (1, 2) | (1, 4) =>
+						try!(write!(output, "\x1B[?1;0;{}S", sixel::REGISTERS)),
+
+					(2, 1) | (2, 2) | (2, 4) | (3, 1) | (3, 2) | (3, 4) =>
+						try!(write!(output, "\x1B[?{};0;{};{}S", arg(0), width, height)),
+
+					(1, 3) | (2, 3) | (3, 3) =>
+						try!(write!(output, "\x1B[?{};3;0S", arg(0))),
+
+					(1, _) | (2, _) | (3, _) =>
+						try!(write!(output, "\x1B[?{};2;0S", arg(0))),
+
+					(item, _) =>
+						try!(write!(output, "\x1B[?{};1;0S", item)),
+				}
+			}
+
+			// Save private modes.
+			Control::C1(C1::ControlSequence(CSI::Private(b's', None, args))) => {
+				for mode in args.into_iter().flat_map(Option::into_iter) {
+					if let Some(value) = self.query(true, mode) {
+						self.modes.insert(mode, value);
+					}
+				}
+			}
+
+			// Restore private modes.
+			Control::C1(C1::ControlSequence(CSI::Private(b'r', None, args))) => {
+				for mode in args.into_iter().flat_map(Option::into_iter) {
+					let value = try!(continue option self.modes.get(&mode).cloned());
+
+					actions.extend(try!(self.control(match DEC::Mode::parse(mode) {
+						Ok(mode) if value =>
+							Control::DEC(DEC::Set(iter::once(mode).collect())),
+
+						Ok(mode) =>
+							Control::DEC(DEC::Reset(iter::once(mode).collect())),
+
+						Err(..) =>
+							Control::C1(C1::ControlSequence(CSI::Private(if value { b'h' } else { b'l' }, None,
+								iter::once(Some(mode)).collect()))),
+					}, output.by_ref())));
+				}
+			}
+
 			Control::DEC(DEC::ApplicationKeypad(true)) => {
 				self.mode.insert(mode::APPLICATION_KEYPAD);
 			}
@@ -1192,6 +1950,12 @@ impl Terminal {
 				self.mode.remove(mode::APPLICATION_KEYPAD);
 			}
 
+			// With left and right margins enabled the sequence sets the margins.
+			Control::C1(C1::ControlSequence(CSI::SaveCursor)) if self.mode.contains(mode::MARGINS) => {
+				self.cursor.margin = (0, self.region.width - 1);
+				term!(self; cursor Position(Some(0), Some(0)));
+			}
+
 			Control::C1(C1::ControlSequence(CSI::SaveCursor)) |
 			Control::DEC(DEC::SaveCursor) => {
 				self.saved = Some(self.cursor.clone());
@@ -1208,7 +1972,7 @@ impl Terminal {
 
 			// Charset.
 			Control::DEC(DEC::SelectCharset(i, charset)) => {
-				if self.cursor.charsets.len() >= i as usize {
+				if self.cursor.charsets.len() > i as usize {
 					self.cursor.charsets[i as usize] = charset;
 				}
 			}
@@ -1221,9 +1985,18 @@ impl Terminal {
 				self.cursor.charset = 1;
 			}
 
+			Control::C1(C1::SingleShiftTwo) => {
+				self.cursor.shift = Some(2);
+			}
+
+			Control::C1(C1::SingleShiftThree) => {
+				self.cursor.shift = Some(3);
+			}
+
 			// Movement functions.
+			// Moving left stops at the left margin.
 			Control::C0(C0::CarriageReturn) => {
-				term!(self; cursor Position(Some(0), None));
+				term!(self; cursor Left(self.cursor.x()));
 			}
 
 			Control::C0(C0::LineFeed) => {
@@ -1327,6 +2100,8 @@ impl Terminal {
 					}
 
 					self.grid.wrapped(y, false);
+					self.grid.unmark(y);
+					self.grid.size(y, row::Size::Single);
 					self.touched.line(y);
 				}
 			}
@@ -1345,6 +2120,8 @@ impl Terminal {
 					}
 
 					self.grid.wrapped(y, false);
+					self.grid.unmark(y);
+					self.grid.size(y, row::Size::Single);
 					self.touched.line(y);
 				}
 			}
@@ -1356,8 +2133,12 @@ impl Terminal {
 					}
 
 					self.grid.wrapped(y, false);
+					self.grid.unmark(y);
+					self.grid.size(y, row::Size::Single);
 				}
 
+				self.grid.unplace(false, |_, _| true);
+
 				self.touched.all();
 			}
 
@@ -1391,9 +2172,68 @@ impl Terminal {
 				}
 
 				self.grid.wrapped(y, false);
+				self.grid.unmark(y);
 				self.touched.line(y);
 			}
 
+			// Selective erase functions.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'q', Some(b'"'), args))) => {
+				let mut style = **self.cursor.style();
+
+				match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					1 =>
+						style.attributes.insert(style::PROTECTED),
+
+					0 | 2 =>
+						style.attributes.remove(style::PROTECTED),
+
+					_ => ()
+				}
+
+				self.cursor.update(style);
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Private(b'J', None, args))) => {
+				let (x, y) = term!(self; cursor);
+
+				match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					0 => {
+						self.selective(y, (x, self.region.width - 1));
+
+						for y in y + 1 .. self.region.height {
+							self.selective(y, (0, self.region.width - 1));
+						}
+					}
+
+					1 => {
+						for y in 0 .. y {
+							self.selective(y, (0, self.region.width - 1));
+						}
+
+						self.selective(y, (0, x));
+					}
+
+					2 => {
+						for y in 0 .. self.region.height {
+							self.selective(y, (0, self.region.width - 1));
+						}
+					}
+
+					_ => ()
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Private(b'K', None, args))) => {
+				let (x, y) = term!(self; cursor);
+
+				match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					0 => self.selective(y, (x, self.region.width - 1)),
+					1 => self.selective(y, (0, x)),
+					2 => self.selective(y, (0, self.region.width - 1)),
+					_ => ()
+				}
+			}
+
 			Control::C1(C1::ControlSequence(CSI::EraseCharacter(n))) => {
 				let (x, y) = term!(self; cursor);
 
@@ -1406,35 +2246,226 @@ impl Terminal {
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeleteLine(n))) => {
-				term!(self; scroll up n from self.cursor.y());
+				if term!(self; inside margins) {
+					term!(self; scroll up n from self.cursor.y());
+				}
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeleteCharacter(n))) => {
 				let (x, y) = term!(self; cursor);
-				self.grid.delete(x, y, n);
+
+				if !term!(self; inside margins) {
+					return Ok(actions);
+				}
+
+				self.grid.delete(x, y, n, term!(self; margins).map(|m| m.1));
 
 				for x in x .. self.region.width {
 					self.touched.mark(x, y);
 				}
 			}
 
+			// Line size functions.
+			Control::DEC(DEC::SingleWidth) =>
+				self.line(row::Size::Single),
+
+			Control::DEC(DEC::DoubleWidth) =>
+				self.line(row::Size::Double),
+
+			Control::DEC(DEC::Double(DEC::Half::Top)) =>
+				self.line(row::Size::Top),
+
+			Control::DEC(DEC::Double(DEC::Half::Bottom)) =>
+				self.line(row::Size::Bottom),
+
+			// Rectangular area functions.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'x', Some(b'$'), args))) => {
+				let ch = match args.get(0).and_then(|v| *v) {
+					Some(ch @ 32 ... 126) | Some(ch @ 160 ... 255) =>
+						char::from_u32(ch),
+
+					_ =>
+						None
+				};
+
+				// The character is the first argument, so the area is only there when
+				// it is.
+				if let Some(ch) = ch {
+					if let Some(area) = self.rectangle(&args[1..]) {
+						let (left, top, right, bottom) = area;
+						let value                      = ch.to_string();
+
+						self.touch(area);
+
+						for y in top ... bottom {
+							for x in left ... right {
+								self.grid[(x, y)].make_occupied(value.clone(), self.cursor.style().clone());
+							}
+
+							self.grid.clean_edges(y, (left, right));
+						}
+					}
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'z', Some(b'$'), args))) => {
+				if let Some(area) = self.rectangle(&args) {
+					let (left, top, right, bottom) = area;
+
+					self.touch(area);
+
+					for y in top ... bottom {
+						for x in left ... right {
+							self.grid[(x, y)].make_empty(self.cursor.style().clone());
+						}
+
+						self.grid.clean_edges(y, (left, right));
+					}
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'{', Some(b'$'), args))) => {
+				if let Some((left, top, right, bottom)) = self.rectangle(&args) {
+					for y in top ... bottom {
+						self.selective(y, (left, right));
+					}
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'v', Some(b'$'), args))) => {
+				let source      = self.rectangle(&args);
+				let destination = self.rectangle(&[
+					args.get(5).cloned().unwrap_or(None),
+					args.get(6).cloned().unwrap_or(None)]);
+
+				if let (Some((left, top, right, bottom)), Some((x, y, limit_x, limit_y))) = (source, destination) {
+					let width  = cmp::min(right - left, limit_x - x);
+					let height = cmp::min(bottom - top, limit_y - y);
+
+					// Copy the source first, since the areas may overlap.
+					let mut cells = Vec::with_capacity(((width + 1) * (height + 1)) as usize);
+					for y in top ... top + height {
+						for x in left ... left + width {
+							cells.push(self.grid[(x, y)].clone());
+						}
+					}
+
+					self.touch((x, y, x + width, y + height));
+
+					let mut cells = cells.into_iter();
+					for y in y ... y + height {
+						for x in x ... x + width {
+							self.grid[(x, y)] = cells.next().unwrap();
+						}
+
+						self.grid.clean_edges(y, (x, x + width));
+					}
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'r', Some(b'$'), ref args))) |
+			Control::C1(C1::ControlSequence(CSI::Unknown(b't', Some(b'$'), ref args))) => {
+				let reverse = if let Control::C1(C1::ControlSequence(CSI::Unknown(b't', ..))) = control { true } else { false };
+
+				let mut insert = style::Attributes::empty();
+				let mut remove = style::Attributes::empty();
+				let mut toggle = style::Attributes::empty();
+
+				for attr in args.iter().skip(4).map(|v| v.unwrap_or(0)) {
+					let (on, value) = match attr {
+						0  => (false, style::BOLD | style::UNDERLINES | style::BLINK | style::REVERSE),
+						1  => (true, style::BOLD),
+						4  => (true, style::UNDERLINE),
+						5  => (true, style::BLINK),
+						7  => (true, style::REVERSE),
+						8  => (true, style::INVISIBLE),
+						22 => (false, style::BOLD),
+						24 => (false, style::UNDERLINES),
+						25 => (false, style::BLINK),
+						27 => (false, style::REVERSE),
+						28 => (false, style::INVISIBLE),
+						_  => continue,
+					};
+
+					if reverse {
+						if on || attr == 0 {
+							toggle.toggle(value);
+						}
+					}
+					else if on {
+						insert.insert(value);
+						remove.remove(value);
+					}
+					else {
+						remove.insert(value);
+						insert.remove(value);
+					}
+				}
+
+				if let Some((left, top, right, bottom)) = self.rectangle(&args) {
+					// Neighbouring cells usually share the style, so reuse the last one.
+					let mut last: Option<(Rc<Style>, Rc<Style>)> = None;
+
+					for y in top ... bottom {
+						for x in left ... right {
+							let cell = &mut self.grid[(x, y)];
+
+							if cell.is_reference() {
+								continue;
+							}
+
+							if let Some((ref from, ref to)) = last {
+								if **from == **cell.style() {
+									cell.set_style(to.clone());
+									continue;
+								}
+							}
+
+							let from      = cell.style().clone();
+							let mut style = *from;
+							style.attributes.remove(remove);
+							style.attributes.insert(insert);
+							style.attributes.toggle(toggle);
+
+							let to = Rc::new(style);
+							cell.set_style(to.clone());
+							last = Some((from, to));
+						}
+					}
+
+					for (x, y) in Region::from(left, top, right - left + 1, bottom - top + 1).absolute() {
+						self.touched.mark(x, y);
+					}
+				}
+			}
+
 			// Insertion functions.
 			Control::DEC(DEC::AlignmentTest) => {
 				for (x, y) in self.region.absolute() {
 					self.grid[(x, y)].make_occupied("E", self.cursor.style().clone());
 				}
 
+				for y in 0 .. self.region.height {
+					self.grid.size(y, row::Size::Single);
+				}
+
 				self.touched.all();
 			}
 
 			Control::C1(C1::ControlSequence(CSI::InsertLine(n))) => {
-				term!(self; scroll down n from self.cursor.y());
+				if term!(self; inside margins) {
+					term!(self; scroll down n from self.cursor.y());
+				}
 			}
 
 			Control::C1(C1::ControlSequence(CSI::InsertCharacter(n))) => {
 				let (x, y) = term!(self; cursor);
 
-				self.grid.insert(x, y, n);
+				if !term!(self; inside margins) {
+					return Ok(actions);
+				}
+
+				self.grid.insert(x, y, n, term!(self; margins).map(|m| m.1));
 
 				for x in x .. self.region.width {
 					self.touched.mark(x, y);
@@ -1525,13 +2556,13 @@ impl Terminal {
 
 							SGR::Font(SGR::Weight::Normal) | SGR::Font(SGR::Weight::Faint) => {
 								if let Some(n) = self.cursor.bright {
-									style.foreground = Some(*self.config.color().get(n));
+									style.foreground = Some(*self.palette.get(n));
 								}
 							}
 
 							SGR::Font(SGR::Weight::Bold) => {
 								if let Some(n) = self.cursor.bright {
-									style.foreground = Some(*self.config.color().get(n + 8));
+									style.foreground = Some(*self.palette.get(n + 8));
 								}
 							}
 
@@ -1540,18 +2571,25 @@ impl Terminal {
 					}
 
 					match attr {
-						SGR::Reset =>
-							style = Style::default(),
+						// Character protection is not a graphic rendition.
+						SGR::Reset => {
+							let protected = style.attributes & style::PROTECTED;
+
+							style = Style::default();
+							style.attributes.insert(protected);
+						}
 
 						SGR::Italic(true) =>
 							style.attributes.insert(style::ITALIC),
 						SGR::Italic(false) =>
 							style.attributes.remove(style::ITALIC),
 
-						SGR::Underline(true) =>
-							style.attributes.insert(style::UNDERLINE),
+						SGR::Underline(true) => {
+							style.attributes.remove(style::UNDERLINES);
+							style.attributes.insert(style::UNDERLINE);
+						}
 						SGR::Underline(false) =>
-							style.attributes.remove(style::UNDERLINE),
+							style.attributes.remove(style::UNDERLINES),
 
 						SGR::Blink(true) =>
 							style.attributes.insert(style::BLINK),
@@ -1587,19 +2625,19 @@ impl Terminal {
 						}
 
 						SGR::Foreground(SGR::Color::Default) =>
-							style.foreground = Some(*self.config.style().color().foreground()),
+							style.foreground = Some(*self.palette.foreground()),
 
 						SGR::Foreground(SGR::Color::Index(n)) =>
-							style.foreground = Some(*self.config.color().get(n)),
+							style.foreground = Some(*self.palette.get(n)),
 
 						SGR::Foreground(ref color) =>
 							style.foreground = Some(to_rgba(color)),
 
 						SGR::Background(SGR::Color::Default) =>
-							style.background = Some(*self.config.style().color().background()),
+							style.background = Some(*self.palette.background()),
 
 						SGR::Background(SGR::Color::Index(n)) =>
-							style.background = Some(*self.config.color().get(n)),
+							style.background = Some(*self.palette.get(n)),
 
 						SGR::Background(ref color) =>
 							style.background = Some(to_rgba(color)),
@@ -1666,12 +2704,15 @@ impl Terminal {
 				self.command = Some(Command::Device);
 			}
 
+			Control::C1(C1::ApplicationProgramCommand) => {
+				self.command = Some(Command::Application(Vec::new()));
+			}
+
 			Control::C1(C1::String) |
 			Control::C1(C1::PrivacyMessage) |
-			Control::C1(C1::ApplicationProgramCommand) |
 			Control::C1(C1::SingleCharacter) |
 			Control::C1(C1::OperatingSystemCommand) => {
-				self.command = Some(Command::Internal);
+				self.command = Some(Command::Internal(Vec::new()));
 			}
 
 			code => {
@@ -1682,142 +2723,1183 @@ impl Terminal {
 		Ok(actions)
 	}
 
-	fn insert<T: AsRef<str>>(&mut self, ch: T) {
-		let mut ch = ch.as_ref();
+	/// Draw ReGIS graphics over the screen, the drawn cells become images.
+	fn regis(&mut self, regis: Regis) {
+		let (width, height) = self.font;
+		let mut canvas      = cairo::Image::new(self.region.width * width, self.region.height * height);
 
-		// Convert from normal characters to graphical characters if the charset is
-		// enabled.
-		if term!(self; charset) == DEC::Charset::DEC(DEC::charset::DEC::Graphic) {
-			ch = match ch {
-				"A" => "↑",
-				"B" => "↓",
-				"C" => "→",
-				"D" => "←",
-				"E" => "█",
-				"F" => "▚",
-				"G" => "☃",
-				"_" => " ",
-				"`" => "◆",
-				"a" => "▒",
-				"b" => "␉",
-				"c" => "␌",
-				"d" => "␍",
-				"e" => "␊",
-				"f" => "°",
-				"g" => "±",
-				"h" => "␤",
-				"i" => "␋",
-				"j" => "┘",
-				"k" => "┐",
-				"l" => "┌",
-				"m" => "└",
-				"n" => "┼",
-				"o" => "⎺",
-				"p" => "⎻",
-				"q" => "─",
-				"r" => "⎼",
-				"s" => "⎽",
-				"t" => "├",
-				"u" => "┤",
-				"v" => "┴",
-				"w" => "┬",
-				"x" => "│",
-				"y" => "≤",
-				"z" => "≥",
-				"{" => "π",
-				"|" => "≠",
-				"}" => "£",
-				"~" => "·",
-				_   => ch,
-			};
+		// Start from the graphics already on screen.
+		for (x, y) in self.region.absolute() {
+			if self.grid[(x, y)].is_image() {
+				canvas.paste(self.grid[(x, y)].image(), x * width, y * height);
+			}
 		}
 
-		let width = ch.width() as u32;
+		let erased = regis.draw(&mut canvas);
 
-		// Bail out if it cannot be displayed.
-		if width == 0 {
-			return;
-		}
+		for (x, y) in self.region.absolute() {
+			let tile = canvas.crop(x * width, y * height, width, height);
 
-		// Wrap to the next line if needed.
-		if self.mode.contains(mode::WRAP) && self.cursor.wrap() {
-			if term!(self; cursor Down(1)).is_some() {
-				term!(self; scroll! up 1);
+			if !tile.is_empty() {
+				self.grid[(x, y)].make_image(tile, self.cursor.style().clone());
 			}
-
-			term!(self; cursor Position(Some(0), None));
-			let (_, y) = term!(self; cursor);
-			self.grid.wrapped(y, true);
-		}
-
-		let (x, y) = term!(self; cursor);
-
-		// If the character width goes beyond the terminal width, make the cells empty.
-		if x + width > self.region.width {
-			for x in x .. self.region.width {
+			else if erased || self.grid[(x, y)].is_image() {
 				self.grid[(x, y)].make_empty(self.cursor.style().clone());
-				self.touched.mark(x, y);
 			}
-		}
-		// If the inserted character is all whitespace make the cell empty.
-		else if ch.chars().all(char::is_whitespace) {
-			for x in x .. x + width {
-				self.grid[(x, y)].make_empty(self.cursor.style().clone());
-				self.touched.mark(x, y);
+			else {
+				continue;
 			}
 
-			term!(self; clean references (x + width, y));
-		}
-		// Just insert the grapheme.
-		else {
-			self.grid[(x, y)].make_occupied(ch, self.cursor.style().clone());
+			self.grid.clean_edges(y, (x, x));
 			self.touched.mark(x, y);
-
-			for (i, x) in (x + 1 .. x + width).enumerate() {
-				self.grid[(x, y)].make_reference(i as u8 + 1);
-			}
-
-			term!(self; clean references (x + width, y));
 		}
+	}
 
-		// If the character overflows the region, mark it for wrapping.
-		if x + width >= self.region.width {
-			self.cursor.state.insert(cursor::WRAP);
-		}
-		else {
-			term!(self; cursor Right(width));
+	/// Move the drawn sixel grid into the terminal.
+	fn sixel(&mut self, sixel: Sixel) {
+		if !self.mode.contains(mode::SIXEL_PRIVATE) {
+			self.registers = sixel.registers().clone();
 		}
-	}
 
-	fn command(&mut self, command: &str) -> Vec<Action> {
-		let mut actions = Vec::new();
+		let (edge, top) = sixel.origin();
 
-		match command {
-			cmd if cmd.starts_with("0;") ||
-			       cmd.starts_with("1;") ||
-			       cmd.starts_with("2;") ||
-			       cmd.starts_with("k;") => {
-				actions.push(Action::Title(String::from(&cmd[2..])));
-			}
+		// In display mode the cursor doesn't move and the image never scrolls.
+		if self.mode.contains(mode::SIXEL_DISPLAY) {
+			for (i, row) in sixel.into_inner().into_iter().enumerate() {
+				let y = top + i as u32;
 
-			cmd if cmd.starts_with("cursor:") => {
-				let mut parts = cmd.split(':').skip(1);
+				if y >= self.region.height {
+					break;
+				}
 
-				match parts.next() {
-					Some("fg") => {
-						let     desc  = parts.next().unwrap_or("-");
-						let mut color = *self.config.style().cursor().foreground();
+				let mut right = edge;
 
-						if let Some(c) = config::util::to_color(desc) {
-							color = c;
-						}
+				for (i, buffer) in row.into_iter().enumerate() {
+					let x = edge + i as u32;
 
-						self.cursor.foreground = color;
+					if x >= self.region.width {
+						break;
 					}
 
-					Some("bg") => {
-						let     desc  = parts.next().unwrap_or("-");
-						let mut color = *self.config.style().cursor().background();
+					self.grid[(x, y)].make_image(buffer, self.cursor.style().clone());
+					self.touched.mark(x, y);
+					right = x;
+				}
+
+				self.grid.clean_edges(y, (edge, right));
+			}
+
+			return;
+		}
+
+		self.draw(sixel.into_inner());
+	}
+
+	/// Draw a grid of image buffers at the cursor, scrolling as needed, the
+	/// cursor ends up after the last buffer.
+	fn draw(&mut self, tiles: Vec<Vec<cairo::Image>>) {
+		let rows = tiles.len();
+		let edge = term!(self; cursor).0;
+
+		for (i, row) in tiles.into_iter().enumerate() {
+			for buffer in row {
+				let (x, y) = term!(self; cursor);
+				self.grid[(x, y)].make_image(buffer, self.cursor.style().clone());
+				term!(self; cursor Right(1));
+			}
+
+			// Clean leftover references.
+			let (x, y) = term!(self; cursor);
+			self.grid.clean_references(x, y);
+
+			// If it's the last row, skip cursor movement.
+			if i == rows - 1 {
+				continue;
+			}
+
+			if term!(self; cursor Down(1)).is_some() {
+				term!(self; scroll! up 1);
+			}
+
+			term!(self; cursor Position(Some(edge), None));
+		}
+	}
+
+	/// Handle a graphics command, answering with the outcome unless asked to
+	/// be quiet.
+	fn graphics<O: Write>(&mut self, string: &str, mut output: O) -> io::Result<()> {
+		let command = if let Some(command) = self.graphics.feed(string) {
+			command
+		}
+		else {
+			return Ok(());
+		};
+
+		debug!(target: "cancer::terminal::graphics", "graphics action {:?}", command.action() as char);
+
+		let result = match command.action() {
+			b'q' =>
+				graphics::decode(&command).map(|_| command.number(b'i', 0)),
+
+			b't' =>
+				self.graphics.load(&command).map(|(id, _)| id),
+
+			b'T' =>
+				self.graphics.load(&command).and_then(|(id, image)| self.place(id, image, &command)),
+
+			b'p' =>
+				self.graphics.find(&command).and_then(|(id, image)| self.place(id, image, &command)),
+
+			b'd' => {
+				self.unplace(&command);
+				return Ok(());
+			}
+
+			_ =>
+				Err(graphics::Error::Unsupported)
+		};
+
+		// Only commands with an ID or a number get an answer.
+		let (id, number, quiet) = (command.number(b'i', 0), command.number(b'I', 0), command.number(b'q', 0));
+
+		if id == 0 && number == 0 {
+			return Ok(());
+		}
+
+		let message = match result {
+			Ok(..) if quiet >= 1  => return Ok(()),
+			Err(..) if quiet >= 2 => return Ok(()),
+			Ok(..)                => "OK",
+			Err(error)            => error.code(),
+		};
+
+		// Images transmitted with just a number get the assigned ID back.
+		let mut keys = Vec::new();
+		let     id   = if id != 0 { id } else { result.unwrap_or(0) };
+
+		if id != 0 {
+			keys.push(format!("i={}", id));
+		}
+
+		if number != 0 {
+			keys.push(format!("I={}", number));
+		}
+
+		if command.number(b'p', 0) != 0 {
+			keys.push(format!("p={}", command.number(b'p', 0)));
+		}
+
+		write!(output, "\x1B_G{};{}\x1B\\", keys.join(","), message)
+	}
+
+	/// Place an image at the cursor, moving the cursor past it unless asked not
+	/// to.
+	fn place(&mut self, id: u32, image: Rc<cairo::Image>, command: &graphics::Command) -> Result<u32, graphics::Error> {
+		let (x, y)    = term!(self; cursor);
+		let placement = Rc::new(try!(graphics::Placement::new(id, image, x, command, self.font)));
+
+		// Placing an image again with the same placement ID replaces it.
+		if placement.id() != 0 {
+			let removed = self.grid.unplace(true, |_, p| p.image() == id && p.id() == placement.id());
+
+			if !removed.is_empty() {
+				self.touched.all();
+			}
+		}
+
+		for row in y .. cmp::min(y + placement.rows(), self.region.height) {
+			for column in x .. cmp::min(x + placement.columns(), self.region.width) {
+				self.touched.mark(column, row);
+			}
+		}
+
+		let (columns, rows) = (placement.columns(), placement.rows());
+		self.grid.place(y, placement);
+
+		// The cursor ends up after the image on its last row, scrolling at most a
+		// screen.
+		if command.number(b'C', 0) != 1 {
+			for _ in 1 .. cmp::min(rows, self.region.height) {
+				if term!(self; cursor Down(1)).is_some() {
+					term!(self; scroll! up 1);
+				}
+			}
+
+			term!(self; cursor Position(Some(cmp::min(x + columns, self.region.width - 1)), None));
+		}
+
+		Ok(id)
+	}
+
+	/// Remove the placements selected by a graphics command, uppercase targets
+	/// also drop the image data.
+	fn unplace(&mut self, command: &graphics::Command) {
+		let target = command.character(b'd', b'a');
+		let cursor = term!(self; cursor);
+		let cell   = (command.number(b'x', 1).saturating_sub(1), command.number(b'y', 1).saturating_sub(1));
+		let image  = match target {
+			b'n' | b'N' => self.graphics.number(command.number(b'I', 0)).unwrap_or(0),
+			_           => command.number(b'i', 0),
+		};
+
+		let (placement, z) = (command.number(b'p', 0), command.integer(b'z', 0));
+
+		let removed = match target {
+			b'a' | b'A' =>
+				self.grid.unplace(false, |_, _| true),
+
+			b'i' | b'I' | b'n' | b'N' =>
+				self.grid.unplace(true, |_, p| p.image() == image && (placement == 0 || p.id() == placement)),
+
+			b'c' | b'C' =>
+				self.grid.unplace(false, |y, p| p.covers(y, cursor)),
+
+			b'p' | b'P' =>
+				self.grid.unplace(false, |y, p| p.covers(y, cell)),
+
+			b'x' | b'X' =>
+				self.grid.unplace(false, |y, p| p.covers(y, (cell.0, cmp::max(y, 0) as u32))),
+
+			b'y' | b'Y' =>
+				self.grid.unplace(false, |y, p| p.covers(y, (p.x(), cell.1))),
+
+			b'z' | b'Z' =>
+				self.grid.unplace(false, |_, p| p.z() == z),
+
+			_ =>
+				Vec::new()
+		};
+
+		if !removed.is_empty() {
+			self.touched.all();
+		}
+
+		if let b'A' ... b'Z' = target {
+			let mut ids = removed.iter().map(|p| p.image()).collect::<Vec<_>>();
+			ids.push(image);
+			drop(removed);
+
+			for id in ids {
+				self.graphics.remove(id);
+			}
+		}
+	}
+
+	/// Report a status string.
+	fn status<O: Write>(&self, request: &str, mut output: O) -> io::Result<()> {
+		match request {
+			"m" => {
+				let     style = self.cursor.style();
+				let mut sgr   = String::from("0");
+
+				for &(attribute, code) in &[
+					(style::BOLD, 1), (style::FAINT, 2), (style::ITALIC, 3),
+					(style::UNDERLINE, 4), (style::BLINK, 5), (style::REVERSE, 7),
+					(style::INVISIBLE, 8), (style::STRUCK, 9), (style::OVERLINE, 53)]
+				{
+					if style.attributes().contains(attribute) {
+						sgr.push_str(&format!(";{}", code));
+					}
+				}
+
+				for &(attribute, code) in &[
+					(style::UNDERLINE_DOUBLE, 2), (style::UNDERLINE_CURLY, 3),
+					(style::UNDERLINE_DOTTED, 4), (style::UNDERLINE_DASHED, 5)]
+				{
+					if style.attributes().contains(attribute) {
+						sgr.push_str(&format!(";4:{}", code));
+					}
+				}
+
+				// Colors are stored resolved, so indexed ones are looked up again in
+				// the palette and the defaults are left out.
+				let index = |color: &Rgba<f64>|
+					(0 .. 256).find(|&n| self.palette.get(n as u8) == color).map(|n| n as u8);
+
+				let rgb = |color: &Rgba<f64>|
+					((color.red * 255.0) as u8, (color.green * 255.0) as u8, (color.blue * 255.0) as u8);
+
+				if let Some(n) = self.cursor.bright {
+					sgr.push_str(&format!(";{}", 30 + n as u32));
+				}
+				else if let Some(color) = style.foreground() {
+					if color != self.palette.foreground() {
+						match index(color) {
+							Some(n) if n < 8 =>
+								sgr.push_str(&format!(";{}", 30 + n as u32)),
+
+							Some(n) if n < 16 =>
+								sgr.push_str(&format!(";{}", 90 + n as u32 - 8)),
+
+							Some(n) =>
+								sgr.push_str(&format!(";38;5;{}", n)),
+
+							None => {
+								let (r, g, b) = rgb(color);
+								sgr.push_str(&format!(";38;2;{};{};{}", r, g, b));
+							}
+						}
+					}
+				}
+
+				if let Some(color) = style.background() {
+					if color != self.palette.background() {
+						match index(color) {
+							Some(n) if n < 8 =>
+								sgr.push_str(&format!(";{}", 40 + n as u32)),
+
+							Some(n) if n < 16 =>
+								sgr.push_str(&format!(";{}", 100 + n as u32 - 8)),
+
+							Some(n) =>
+								sgr.push_str(&format!(";48;5;{}", n)),
+
+							None => {
+								let (r, g, b) = rgb(color);
+								sgr.push_str(&format!(";48;2;{};{};{}", r, g, b));
+							}
+						}
+					}
+				}
+
+				if let Some(color) = style.underline() {
+					match index(color) {
+						Some(n) =>
+							sgr.push_str(&format!(";58:5:{}", n)),
+
+						None => {
+							let (r, g, b) = rgb(color);
+							sgr.push_str(&format!(";58:2::{}:{}:{}", r, g, b));
+						}
+					}
+				}
+
+				write!(output, "\x1BP1$r{}m\x1B\\", sgr)
+			}
+
+			"r" => {
+				write!(output, "\x1BP1$r{};{}r\x1B\\", self.cursor.scroll.0 + 1, self.cursor.scroll.1 + 1)
+			}
+
+			"s" => {
+				write!(output, "\x1BP1$r{};{}s\x1B\\", self.cursor.margin.0 + 1, self.cursor.margin.1 + 1)
+			}
+
+			" q" => {
+				let blink = self.cursor.state.contains(cursor::BLINK);
+
+				write!(output, "\x1BP1$r{} q\x1B\\", match self.cursor.shape {
+					Shape::Block => if blink { 1 } else { 2 },
+					Shape::Line  => if blink { 3 } else { 4 },
+					Shape::Beam  => if blink { 5 } else { 6 },
+				})
+			}
+
+			"\"q" => {
+				write!(output, "\x1BP1$r{}\"q\x1B\\",
+					if self.cursor.style().attributes.contains(style::PROTECTED) { 1 } else { 0 })
+			}
+
+			"\"p" => {
+				write!(output, "\x1BP1$r{};1\"p\x1B\\", 60 + self.config.environment().device().level() as u32)
+			}
+
+			_ =>
+				output.write_all(b"\x1BP0$r\x1B\\")
+		}
+	}
+
+	/// Query the state of an ANSI or private mode, `None` if the mode is not
+	/// supported.
+	fn query(&self, private: bool, mode: u32) -> Option<bool> {
+		if private {
+			match mode {
+				1    => Some(self.mode.contains(mode::APPLICATION_CURSOR)),
+				3    => Some(self.region.width == 132),
+				5    => Some(self.mode.contains(mode::REVERSE)),
+				6    => Some(self.cursor.state.contains(cursor::ORIGIN)),
+				7    => Some(self.mode.contains(mode::WRAP)),
+				25   => Some(self.cursor.state.contains(cursor::VISIBLE)),
+				66   => Some(self.mode.contains(mode::APPLICATION_KEYPAD)),
+				69   => Some(self.mode.contains(mode::MARGINS)),
+				80   => Some(self.mode.contains(mode::SIXEL_DISPLAY)),
+				9    => Some(self.mode.contains(mode::MOUSE_X10)),
+				1000 => Some(self.mode.contains(mode::MOUSE_BUTTON)),
+				1002 => Some(self.mode.contains(mode::MOUSE_MOTION)),
+				1003 => Some(self.mode.contains(mode::MOUSE_MANY)),
+				1004 => Some(self.mode.contains(mode::FOCUS)),
+				1005 => Some(self.mode.contains(mode::MOUSE_UTF8)),
+				1006 => Some(self.mode.contains(mode::MOUSE_SGR)),
+				1015 => Some(self.mode.contains(mode::MOUSE_URXVT)),
+				1016 => Some(self.mode.contains(mode::MOUSE_PIXELS)),
+				1070 => Some(self.mode.contains(mode::SIXEL_PRIVATE)),
+				2004 => Some(self.mode.contains(mode::BRACKETED_PASTE)),
+				2026 => Some(self.mode.contains(mode::SYNCHRONIZED)),
+
+				47 | 1047 | 1049 =>
+					Some(self.alternate()),
+
+				_ => None
+			}
+		}
+		else {
+			match mode {
+				2  => Some(self.mode.contains(mode::KEYBOARD_LOCK)),
+				4  => Some(self.mode.contains(mode::INSERT)),
+				12 => Some(!self.mode.contains(mode::ECHO)),
+				20 => Some(self.mode.contains(mode::CRLF)),
+
+				_ => None
+			}
+		}
+	}
+
+	/// Get the rectangle described by the `top`, `left`, `bottom` and `right`
+	/// arguments, relative to the margins in origin mode.
+	fn rectangle(&self, args: &[Option<u32>]) -> Option<(u32, u32, u32, u32)> {
+		let (x, y, width, height) = if self.cursor.state.contains(cursor::ORIGIN) {
+			(self.cursor.margin.0, self.cursor.scroll.0,
+			 self.cursor.margin.1 - self.cursor.margin.0 + 1,
+			 self.cursor.scroll.1 - self.cursor.scroll.0 + 1)
+		}
+		else {
+			(0, 0, self.region.width, self.region.height)
+		};
+
+		let arg = |index: usize, default: u32| {
+			let limit = if index % 2 == 0 { height } else { width };
+
+			match args.get(index).and_then(|v| *v) {
+				None | Some(0) => default,
+				Some(n)        => cmp::min(n, limit),
+			}
+		};
+
+		let top    = y + arg(0, 1) - 1;
+		let left   = x + arg(1, 1) - 1;
+		let bottom = y + arg(2, height) - 1;
+		let right  = x + arg(3, width) - 1;
+
+		if top > bottom || left > right {
+			return None;
+		}
+
+		Some((left, top, right, bottom))
+	}
+
+	/// Mark the cells in the rectangle as touched, including wide characters
+	/// crossing its edges.
+	fn touch(&mut self, (left, top, right, bottom): (u32, u32, u32, u32)) {
+		for y in top ... bottom {
+			if let Cell::Reference(offset) = self.grid[(left, y)] {
+				for x in left - offset as u32 .. left {
+					self.touched.mark(x, y);
+				}
+			}
+
+			for x in left ... right {
+				self.touched.mark(x, y);
+			}
+
+			for x in right + 1 .. self.region.width {
+				if self.grid[(x, y)].is_reference() {
+					self.touched.mark(x, y);
+				}
+				else {
+					break;
+				}
+			}
+		}
+	}
+
+	/// Get the color from the arguments of an extended color rendition, either
+	/// `2;r;g;b` with an optional color space before the components, or `5;n`.
+	fn color(&self, args: &[Option<u32>]) -> Option<Rgba<f64>> {
+		match args.get(0).and_then(|v| *v) {
+			Some(2) if args.len() >= 4 => {
+				let rgb = &args[args.len() - 3 ..];
+
+				Some(Rgba::new_u8(
+					rgb[0].unwrap_or(0) as u8,
+					rgb[1].unwrap_or(0) as u8,
+					rgb[2].unwrap_or(0) as u8,
+					255))
+			}
+
+			Some(5) =>
+				args.get(1).and_then(|v| *v).map(|n| *self.palette.get(n as u8)),
+
+			_ =>
+				None
+		}
+	}
+
+	/// Change the line size of the cursor row, dropping the cells that no
+	/// longer fit.
+	fn line(&mut self, size: row::Size) {
+		let y = self.cursor.y();
+
+		self.grid.size(y, size);
+		self.touched.line(y);
+
+		if size != row::Size::Single {
+			let columns = term!(self; columns y);
+
+			for x in columns .. self.region.width {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+			}
+
+			self.grid.clean_edges(y, (0, columns - 1));
+		}
+
+		term!(self; cursor Right(0));
+	}
+
+	/// Erase the unprotected characters in the row between the given columns,
+	/// keeping their attributes.
+	fn selective(&mut self, y: u32, (mut left, mut right): (u32, u32)) {
+		// Wide characters are erased whole.
+		if let Cell::Reference(offset) = self.grid[(left, y)] {
+			left -= offset as u32;
+		}
+
+		while right + 1 < self.region.width && self.grid[(right + 1, y)].is_reference() {
+			right += 1;
+		}
+
+		let mut style = self.cursor.style().clone();
+		for x in left ... right {
+			let cell = &mut self.grid[(x, y)];
+
+			if !cell.is_reference() {
+				style = cell.style().clone();
+			}
+
+			if !style.attributes.contains(style::PROTECTED) {
+				cell.make_empty(style.clone());
+				self.touched.mark(x, y);
+			}
+		}
+	}
+
+	/// Check if a control sequence has to be handled here, because the
+	/// `control` parser doesn't understand it.
+	fn extended(&self, sequence: &Sequence) -> bool {
+		match (sequence.prefix(), sequence.intermediate(), sequence.terminator()) {
+			(Some(b'>'), _, _) | (Some(b'<'), _, _) | (Some(b'='), _, _) =>
+				true,
+
+			// Renditions are split between the two, a plain reset is left to the
+			// `control` parser.
+			(None, None, b'm') =>
+				!sequence.arguments().is_empty(),
+
+			// Without arguments it saves the cursor.
+			(None, None, b's') =>
+				self.mode.contains(mode::MARGINS) && !sequence.arguments().is_empty(),
+
+			_ =>
+				sequence.arguments().iter().any(|a| a.len() > 1)
+		}
+	}
+
+	/// Handle a control sequence the `control` parser doesn't understand.
+	fn sequence<O: Write>(&mut self, sequence: Sequence, mut output: O) -> error::Result<Vec<Action>> {
+		let actions = Vec::new();
+
+		// Graphemes only continue through text.
+		self.grapheme = None;
+
+		match (sequence.prefix(), sequence.intermediate(), sequence.terminator()) {
+			// Set left and right margins.
+			(None, None, b's') if self.mode.contains(mode::MARGINS) => {
+				let mut left  = sequence.arg(0, 1).saturating_sub(1);
+				let mut right = sequence.arg(1, self.region.width).saturating_sub(1);
+
+				left  = util::clamp(left, 0, self.region.width - 1);
+				right = util::clamp(right, 0, self.region.width - 1);
+
+				if left < right {
+					self.cursor.margin = (left, right);
+					term!(self; cursor Position(Some(0), Some(0)));
+				}
+			}
+
+			// Push keyboard enhancement flags, the stack is kept bounded.
+			(Some(b'>'), None, b'u') => {
+				if self.keyboard.len() >= 16 {
+					self.keyboard.remove(0);
+				}
+
+				self.keyboard.push(Keyboard::from_bits_truncate(sequence.arg(0, 0) as u8));
+			}
+
+			// Pop keyboard enhancement flags.
+			(Some(b'<'), None, b'u') => {
+				let length = self.keyboard.len().saturating_sub(sequence.arg(0, 1) as usize);
+				self.keyboard.truncate(length);
+			}
+
+			// Replace, add or remove the current keyboard enhancement flags.
+			(Some(b'='), None, b'u') => {
+				let flags = Keyboard::from_bits_truncate(sequence.arg(0, 0) as u8);
+
+				if self.keyboard.is_empty() {
+					self.keyboard.push(Keyboard::empty());
+				}
+
+				let current = self.keyboard.last_mut().unwrap();
+				match sequence.arg(1, 1) {
+					1 => *current = flags,
+					2 => current.insert(flags),
+					3 => current.remove(flags),
+					_ => (),
+				}
+			}
+
+			// Set or reset `modifyOtherKeys`.
+			(Some(b'>'), None, b'm') => {
+				if sequence.get(0) == Some(4) {
+					self.modify = sequence.arg(1, 0) as u8;
+				}
+			}
+
+			(Some(b'>'), None, b'n') => {
+				if sequence.get(0) == Some(4) {
+					self.modify = 0;
+				}
+			}
+
+			// Graphic renditions the control parser does not know, like the ones
+			// with sub-parameters or underline colors.
+			(None, None, b'm') => {
+				let mut plain     = Vec::new();
+				let mut arguments = sequence.arguments().iter();
+
+				while let Some(argument) = arguments.next() {
+					let id = argument[0].unwrap_or(0);
+
+					match id {
+						21 | 53 | 55 | 58 | 59 => (),
+
+						// Colors with plain arguments take the following ones.
+						38 | 48 if argument.len() == 1 => {
+							let kind = arguments.next().and_then(|v| v[0]);
+							plain.push(Some(id));
+							plain.push(kind);
+
+							for _ in 0 .. match kind { Some(2) | Some(3) => 3, Some(4) => 4, Some(5) => 1, _ => 0 } {
+								plain.push(arguments.next().and_then(|v| v[0]));
+							}
+
+							continue;
+						}
+
+						_ if argument.len() == 1 => {
+							plain.push(Some(id));
+							continue;
+						}
+
+						_ => ()
+					}
+
+					// Apply the known renditions so far to keep them in order.
+					if !plain.is_empty() {
+						if let Ok(attrs) = SGR::parse(&plain) {
+							try!(self.control(Control::C1(C1::ControlSequence(CSI::SelectGraphicalRendition(attrs))), output.by_ref()));
+						}
+
+						plain.clear();
+					}
+
+					let mut style = **self.cursor.style();
+
+					match id {
+						4 => {
+							style.attributes.remove(style::UNDERLINES);
+							style.attributes.insert(match argument.get(1).and_then(|v| *v).unwrap_or(1) {
+								0 => style::NONE,
+								2 => style::UNDERLINE_DOUBLE,
+								3 => style::UNDERLINE_CURLY,
+								4 => style::UNDERLINE_DOTTED,
+								5 => style::UNDERLINE_DASHED,
+								_ => style::UNDERLINE,
+							});
+						}
+
+						21 => {
+							style.attributes.remove(style::UNDERLINES);
+							style.attributes.insert(style::UNDERLINE_DOUBLE);
+						}
+
+						53 =>
+							style.attributes.insert(style::OVERLINE),
+
+						55 =>
+							style.attributes.remove(style::OVERLINE),
+
+						38 | 48 | 58 => {
+							let color = if argument.len() > 1 {
+								self.color(&argument[1..])
+							}
+							else {
+								let     kind = arguments.next().and_then(|v| v[0]);
+								let mut rest = vec![kind];
+
+								for _ in 0 .. match kind { Some(2) => 3, Some(5) => 1, _ => 0 } {
+									rest.push(arguments.next().and_then(|v| v[0]));
+								}
+
+								self.color(&rest)
+							};
+
+							match id {
+								38 => style.foreground = color.or(style.foreground),
+								48 => style.background = color.or(style.background),
+								_  => style.underline  = color,
+							}
+						}
+
+						59 =>
+							style.underline = None,
+
+						_ => ()
+					}
+
+					self.cursor.update(style);
+				}
+
+				if !plain.is_empty() {
+					if let Ok(attrs) = SGR::parse(&plain) {
+						try!(self.control(Control::C1(C1::ControlSequence(CSI::SelectGraphicalRendition(attrs))), output.by_ref()));
+					}
+				}
+			}
+
+			// Secondary device attributes.
+			(Some(b'>'), None, b'c') if sequence.arg(0, 0) == 0 => {
+				let id = match self.config.environment().device().level() {
+					1 => 0,
+					2 => 1,
+					3 => 24,
+					4 => 41,
+					_ => 64,
+				};
+
+				let version = env!("CARGO_PKG_VERSION_MAJOR").parse::<u32>().unwrap() * 10000 +
+					env!("CARGO_PKG_VERSION_MINOR").parse::<u32>().unwrap() * 100 +
+					env!("CARGO_PKG_VERSION_PATCH").parse::<u32>().unwrap();
+
+				try!(write!(output, "\x1B[>{};{};0c", id, version));
+			}
+
+			// Tertiary device attributes.
+			(Some(b'='), None, b'c') if sequence.arg(0, 0) == 0 => {
+				try!(write!(output, "\x1BP!|{}\x1B\\", self.config.environment().device().id()));
+			}
+
+			// Terminal name and version.
+			(Some(b'>'), None, b'q') if sequence.arg(0, 0) == 0 => {
+				try!(write!(output, "\x1BP>|{}({})\x1B\\", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+			}
+
+			_ =>
+				debug!(target: "cancer::terminal::unhandled", "unhandled sequence: {:?}", sequence)
+		}
+
+		Ok(actions)
+	}
+
+	/// Switch between the primary and alternate screen.
+	fn screen(&mut self, alternate: bool) {
+		if self.alternate() == alternate {
+			return;
+		}
+
+		mem::swap(&mut self.grid, &mut self.inactive);
+		mem::swap(&mut self.keyboard, &mut self.stacked);
+		self.mode.toggle(mode::ALTERNATE);
+		self.touched.all();
+	}
+
+	/// Clear the active screen.
+	fn clear(&mut self) {
+		for y in 0 .. self.region.height {
+			for x in 0 .. self.region.width {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+			}
+
+			self.grid.wrapped(y, false);
+		}
+
+		self.touched.all();
+	}
+
+	fn insert<T: AsRef<str>>(&mut self, ch: T) {
+		let     upper;
+		let mut ch = ch.as_ref();
+
+		// Convert the character from the charset in use, a single shift only
+		// applies to the next character, while outside UTF-8 the upper half of
+		// Latin-1 goes through the charset invoked in GR, unless it's one of the
+		// ISO sets which already are what was received.
+		let right = self.cursor.right.map(|n| self.cursor.charsets[n as usize]).and_then(|c|
+			match c {
+				DEC::Charset::DEC(..) | DEC::Charset::NRCS(..) if !self.mode.contains(mode::UTF8) =>
+					Some(c),
+
+				_ =>
+					None
+			});
+
+		if let Some(n) = self.cursor.shift.take() {
+			ch = charset::convert(self.cursor.charsets[n as usize], ch);
+		}
+		else if let (Some(charset), Some(c @ '\u{A1}' ... '\u{FE}')) = (right, ch.chars().next()) {
+			upper = ((c as u8 - 0x80) as char).to_string();
+			ch    = charset::convert(charset, &upper);
+		}
+		else {
+			ch = charset::convert(term!(self; charset), ch);
+		}
+
+		// Attach the character to the last inserted one if they form a single
+		// grapheme, this covers combining characters and graphemes split across
+		// reads.
+		if let Some((x, y)) = self.grapheme {
+			if ch.len() > 1 && self.grid[(x, y)].is_occupied() &&
+			   format!("{}{}", self.grid[(x, y)].value(), ch).graphemes(true).count() == 1
+			{
+				self.grid[(x, y)].append(ch);
+				self.touched.mark(x, y);
+
+				return;
+			}
+		}
+
+		self.grapheme = None;
+		let width     = ch.width() as u32;
+
+		// Bail out if it cannot be displayed.
+		if width == 0 {
+			return;
+		}
+
+		// Wrap to the next line if needed.
+		if self.mode.contains(mode::WRAP) && self.cursor.wrap() {
+			if term!(self; cursor Down(1)).is_some() {
+				term!(self; scroll! up 1);
+			}
+
+			// Return to the left margin on the next line.
+			term!(self; cursor Left(self.region.width));
+			let (_, y) = term!(self; cursor);
+			self.grid.wrapped(y, true);
+		}
+
+		let (x, y) = term!(self; cursor);
+
+		// The right edge is the right margin, unless the cursor is past it.
+		let right = cmp::min(term!(self; columns y), if x <= self.cursor.margin.1 {
+			self.cursor.margin.1 + 1
+		}
+		else {
+			self.region.width
+		});
+
+		// Shift the rest of the line right when in insert mode.
+		if self.mode.contains(mode::INSERT) && x + width <= right {
+			self.grid.insert(x, y, width, Some(right - 1));
+
+			for x in x .. right {
+				self.touched.mark(x, y);
+			}
+		}
+
+		// If the character width goes beyond the right edge, make the cells empty.
+		if x + width > right {
+			for x in x .. right {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+				self.touched.mark(x, y);
+			}
+		}
+		// If the inserted character is all whitespace make the cell empty.
+		else if ch.chars().all(char::is_whitespace) {
+			for x in x .. x + width {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+				self.touched.mark(x, y);
+			}
+
+			term!(self; clean references (x + width, y));
+		}
+		// Just insert the grapheme.
+		else {
+			self.grid[(x, y)].make_occupied(ch, self.cursor.style().clone());
+			self.grid[(x, y)].set_link(self.link.clone());
+			self.touched.mark(x, y);
+			self.grapheme = Some((x, y));
+
+			for (i, x) in (x + 1 .. x + width).enumerate() {
+				self.grid[(x, y)].make_reference(i as u8 + 1);
+			}
+
+			term!(self; clean references (x + width, y));
+		}
+
+		// If the character overflows the region, mark it for wrapping.
+		if x + width >= right {
+			self.cursor.state.insert(cursor::WRAP);
+		}
+		else {
+			term!(self; cursor Right(width));
+		}
+	}
+
+	fn command<O: Write>(&mut self, command: &str, end: &str, mut output: O) -> error::Result<Vec<Action>> {
+		let mut actions = Vec::new();
+
+		match command {
+			cmd if cmd.starts_with("0;") ||
+			       cmd.starts_with("1;") ||
+			       cmd.starts_with("2;") ||
+			       cmd.starts_with("k;") => {
+				let title = String::from(&cmd[2..]);
+
+				if !cmd.starts_with("2;") && !cmd.starts_with("k;") {
+					self.icon = title.clone();
+				}
+
+				if !cmd.starts_with("1;") {
+					self.title = title.clone();
+				}
+
+				actions.push(Action::Title(title));
+			}
+
+			// Change or query indexed colors.
+			cmd if cmd.starts_with("4;") => {
+				let mut parts = cmd[2..].split(';');
+
+				while let (Some(index), Some(spec)) = (parts.next(), parts.next()) {
+					let index = try!(continue index.parse::<u8>());
+
+					if spec == "?" {
+						try!(write!(output, "\x1B]4;{};{}{}", index, to_spec(self.palette.get(index)), end));
+					}
+					else if let Some(color) = config::util::to_color(spec) {
+						self.palette.set(index, color);
+					}
+				}
+			}
+
+			// Reset indexed colors.
+			cmd if cmd == "104" || cmd.starts_with("104;") => {
+				let indices = cmd[3..].split(';').filter(|v| !v.is_empty())
+					.filter_map(|v| v.parse::<u8>().ok())
+					.collect::<Vec<_>>();
+
+				if indices.is_empty() {
+					for index in 0 ... 255 {
+						self.palette.reset(index);
+					}
+				}
+				else {
+					for index in indices {
+						self.palette.reset(index);
+					}
+				}
+			}
+
+			// Change or query the dynamic colors, following specifications apply to
+			// the next dynamic color.
+			cmd if cmd.starts_with("10;") ||
+			       cmd.starts_with("11;") ||
+			       cmd.starts_with("12;") => {
+				let mut id = cmd[.. 2].parse::<u8>().unwrap();
+
+				for spec in cmd[3..].split(';') {
+					if spec == "?" {
+						let color = match id {
+							10 => *self.palette.foreground(),
+							11 => *self.palette.background(),
+							_  => *self.cursor.background(),
+						};
+
+						try!(write!(output, "\x1B]{};{}{}", id, to_spec(&color), end));
+					}
+					else if let Some(color) = config::util::to_color(spec) {
+						match id {
+							10 => self.palette.set_foreground(Some(color)),
+							11 => self.palette.set_background(Some(color)),
+							_  => self.cursor.background = color,
+						}
+					}
+
+					if id == 12 {
+						break;
+					}
+
+					id += 1;
+				}
+
+				self.touched.all();
+			}
+
+			// Reset the dynamic colors.
+			"110" => {
+				self.palette.set_foreground(None);
+				self.touched.all();
+			}
+
+			"111" => {
+				self.palette.set_background(None);
+				self.touched.all();
+			}
+
+			"112" => {
+				self.cursor.background = *self.config.style().cursor().background();
+				self.touched.push(term!(self; cursor));
+			}
+
+			// Change the current working directory.
+			cmd if cmd.starts_with("7;") => {
+				let url = &cmd[2..];
+
+				if url.is_empty() {
+					self.cwd = None;
+				}
+				else if url.starts_with("file://") {
+					let url = &url[7..];
+
+					// Only local directories that exist are of any use.
+					if let Some(start) = url.find('/') {
+						let host = &url[.. start];
+
+						if host.is_empty() || host == "localhost" || Some(host) == hostname().as_ref().map(AsRef::as_ref) {
+							if let Some(path) = to_path(&url[start..]) {
+								if path.is_dir() {
+									self.cwd = Some(path);
+								}
+							}
+						}
+					}
+				}
+			}
+
+			// Start or end an hyperlink.
+			cmd if cmd.starts_with("8;") => {
+				let mut parts  = cmd[2..].splitn(2, ';');
+				let     params = parts.next().unwrap_or("");
+				let     uri    = parts.next().unwrap_or("");
+
+				if uri.is_empty() {
+					self.link = None;
+				}
+				else {
+					let id = params.split(':')
+						.find(|p| p.starts_with("id="))
+						.map(|p| &p[3..]);
+
+					self.link = Some(self.links.get(id, uri));
+				}
+			}
+
+			// Shell integration marks.
+			cmd if cmd.starts_with("133;") => {
+				let mut parts = cmd[4..].split(';');
+				let     y     = self.cursor.y();
+
+				match parts.next() {
+					Some("A") =>
+						self.grid.mark(y, row::PROMPT),
+
+					Some("B") =>
+						self.grid.mark(y, row::INPUT),
+
+					Some("C") =>
+						self.grid.mark(y, row::OUTPUT),
+
+					Some("D") => {
+						self.grid.mark(y, row::END);
+						self.grid.status(y, parts.next().and_then(|v| v.parse().ok()));
+					}
+
+					_ => ()
+				}
+			}
+
+			// Show an inline image.
+			cmd if cmd.starts_with("1337;File=") => {
+				if let Some(image) = Inline::parse(&cmd[10..]) {
+					let columns = self.region.width - term!(self; cursor).0;
+					let screen  = (self.region.width * self.font.0, self.region.height * self.font.1);
+
+					self.draw(image.draw(self.font, screen, columns));
+				}
+			}
+
+			// Change or query the clipboard.
+			cmd if cmd.starts_with("52;") => {
+				let mut parts = cmd[3..].splitn(2, ';');
+				let     names = parts.next().unwrap_or("");
+				let     data  = parts.next().unwrap_or("");
+
+				let mut clipboards = Vec::new();
+				for name in if names.is_empty() { "s0" } else { names }.chars() {
+					let clipboard = match name {
+						'c'       => Clipboard::System,
+						'p' | 's' => Clipboard::Primary,
+						'q'       => Clipboard::Secondary,
+						_         => continue,
+					};
+
+					if !clipboards.contains(&clipboard) {
+						clipboards.push(clipboard);
+					}
+				}
+
+				// Reading the clipboard leaks it to whatever is running, so it has to
+				// be allowed explicitly.
+				if data == "?" {
+					if !self.config.environment().report().clipboard() {
+						return Ok(actions);
+					}
+
+					if let Some(&clipboard) = clipboards.first() {
+						self.request = Some((match clipboard {
+							Clipboard::System    => 'c',
+							Clipboard::Primary   => 'p',
+							Clipboard::Secondary => 'q',
+						}, end));
+
+						actions.push(Action::Paste(clipboard));
+					}
+				}
+				else if let Some(string) = base64::decode(data).ok().and_then(|v| String::from_utf8(v).ok()) {
+					for clipboard in clipboards {
+						actions.push(Action::Copy(clipboard, string.clone()));
+					}
+				}
+			}
+
+			cmd if cmd.starts_with("cursor:") => {
+				let mut parts = cmd.split(':').skip(1);
+
+				match parts.next() {
+					Some("fg") => {
+						let     desc  = parts.next().unwrap_or("-");
+						let mut color = *self.config.style().cursor().foreground();
+
+						if let Some(c) = config::util::to_color(desc) {
+							color = c;
+						}
+
+						self.cursor.foreground = color;
+					}
+
+					Some("bg") => {
+						let     desc  = parts.next().unwrap_or("-");
+						let mut color = *self.config.style().cursor().background();
 
 						if let Some(c) = config::util::to_color(desc) {
 							color = c;
@@ -1849,8 +3931,50 @@ impl Terminal {
 			_ => ()
 		}
 
-		actions
+		Ok(actions)
+	}
+}
+
+/// Convert a color to an X11 color specification.
+fn to_spec(color: &Rgba<f64>) -> String {
+	format!("rgb:{:04x}/{:04x}/{:04x}",
+		(color.red   * 65535.0) as u16,
+		(color.green * 65535.0) as u16,
+		(color.blue  * 65535.0) as u16)
+}
+
+/// Convert a percent-encoded URL path to a path.
+fn to_path(path: &str) -> Option<PathBuf> {
+	let mut result = Vec::with_capacity(path.len());
+	let mut bytes  = path.bytes();
+
+	while let Some(byte) = bytes.next() {
+		if byte == b'%' {
+			let high = try!(option bytes.next().and_then(|b| (b as char).to_digit(16)));
+			let low  = try!(option bytes.next().and_then(|b| (b as char).to_digit(16)));
+
+			result.push((high << 4 | low) as u8);
+		}
+		else {
+			result.push(byte);
+		}
+	}
+
+	String::from_utf8(result).ok().map(PathBuf::from)
+}
+
+/// Get the name of the local host.
+fn hostname() -> Option<String> {
+	let mut buffer = [0u8; 256];
+
+	unsafe {
+		if libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) != 0 {
+			return None;
+		}
 	}
+
+	let length = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+	String::from_utf8(buffer[.. length].to_vec()).ok()
 }
 
 impl Index<(u32, u32)> for Terminal {
@@ -1860,3 +3984,43 @@ impl Index<(u32, u32)> for Terminal {
 		&self.grid[(x, y)]
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use std::sync::Arc;
+	use config::Config;
+	use style;
+	use super::Terminal;
+
+	fn terminal() -> Terminal {
+		Terminal::new(Arc::new(Config::default()), (8, 16), (80, 24)).unwrap()
+	}
+
+	#[test]
+	fn secondary_attributes() {
+		let mut terminal = terminal();
+		let mut output   = Vec::new();
+
+		terminal.input(b"\x1B[>c", &mut output).unwrap();
+		assert_eq!(&b"\x1B[>41;100;0c"[..], &output[..]);
+	}
+
+	#[test]
+	fn curly_underline() {
+		let mut terminal = terminal();
+		let mut output   = Vec::new();
+
+		terminal.input(b"\x1B[4:3mx", &mut output).unwrap();
+		assert!(terminal.cursor.style().attributes().contains(style::UNDERLINE_CURLY));
+		assert_eq!("x", terminal.grid[(0, 0)].value());
+	}
+
+	#[test]
+	fn keyboard_flags() {
+		let mut terminal = terminal();
+		let mut output   = Vec::new();
+
+		terminal.input(b"\x1B[>1u\x1B[?u\x1B[<u\x1B[?u", &mut output).unwrap();
+		assert_eq!(&b"\x1B[?1u\x1B[?0u"[..], &output[..]);
+	}
+}